@@ -1,12 +1,24 @@
 use std::{net::SocketAddr, sync::Arc};
 
-use axum::{routing::post, Extension, Router};
+use axum::{
+    routing::{get, post},
+    Extension, Router,
+};
 use futures::lock::Mutex;
 use tokio::sync::mpsc;
 
 use crate::{
     message::ApplicationMessage,
-    routes::{close, connect, create_folder, delete_file, list_files, open_asset, rename_file},
+    routes::{
+        apply_preset, asset_image, close, connect, connect_assets, cook_asset, create_folder,
+        delete_asset_instance, delete_file, delete_preset, disconnect_assets,
+        get_parameter_history, get_parameters, insert_multiparm_instance, list_asset_connections,
+        list_asset_definitions, list_files, list_presets, load_asset, open_asset,
+        pick_parameter_path, press_button, redo_parameters, register_asset_definition,
+        remove_multiparm_instance, rename_file, rename_preset, restore_asset_instance,
+        save_asset_instance, save_preset, set_asset_input, set_multiparm_count,
+        set_parameter_expression, set_parameters, undo_parameters,
+    },
     session::SessionRegistry,
 };
 
@@ -27,7 +39,44 @@ pub async fn start_application() -> anyhow::Result<()> {
         .route("/close", post(close))
         .route("/connect", post(connect))
         .route("/open-asset", post(open_asset))
+        .route("/list-asset-definitions", post(list_asset_definitions))
+        .route(
+            "/register-asset-definition",
+            post(register_asset_definition),
+        )
+        .route("/load-asset", post(load_asset))
+        .route("/get-parameters", post(get_parameters))
+        .route("/set-parameters", post(set_parameters))
+        .route("/set-parameter-expression", post(set_parameter_expression))
+        .route("/pick-parameter-path", post(pick_parameter_path))
+        .route(
+            "/insert-multiparm-instance",
+            post(insert_multiparm_instance),
+        )
+        .route(
+            "/remove-multiparm-instance",
+            post(remove_multiparm_instance),
+        )
+        .route("/set-multiparm-count", post(set_multiparm_count))
+        .route("/press-button", post(press_button))
+        .route("/save-preset", post(save_preset))
+        .route("/list-presets", post(list_presets))
+        .route("/apply-preset", post(apply_preset))
+        .route("/rename-preset", post(rename_preset))
+        .route("/delete-preset", post(delete_preset))
+        .route("/undo-parameters", post(undo_parameters))
+        .route("/redo-parameters", post(redo_parameters))
+        .route("/get-parameter-history", post(get_parameter_history))
+        .route("/save-asset-instance", post(save_asset_instance))
+        .route("/restore-asset-instance", post(restore_asset_instance))
+        .route("/delete-asset-instance", post(delete_asset_instance))
+        .route("/cook-asset", post(cook_asset))
+        .route("/set-asset-input", post(set_asset_input))
+        .route("/connect-assets", post(connect_assets))
+        .route("/disconnect-assets", post(disconnect_assets))
+        .route("/list-asset-connections", post(list_asset_connections))
         .route("/list-files", post(list_files))
+        .route("/asset-image", get(asset_image))
         .route("/create-folder", post(create_folder))
         .route("/delete-file", post(delete_file))
         .route("/rename-file", post(rename_file))
@@ -60,7 +109,11 @@ pub async fn start_application() -> anyhow::Result<()> {
                     dialog = dialog.add_filter(name, extensions);
                 }
 
-                let res = dialog.pick_file();
+                let res = if options.pick_folder {
+                    dialog.pick_folder()
+                } else {
+                    dialog.pick_file()
+                };
                 tx_out.send(ApplicationMessage::FileSelected(res)).await?;
             }
             _ => {}
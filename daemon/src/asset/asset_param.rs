@@ -2,28 +2,65 @@
 /// quite verbose, but it is necessary for serializing asset info in HTTP
 /// responses.
 
+use std::collections::BTreeMap;
+
 use hapi_rs::{
     parameter::{ParmBaseTrait, ParmInfo},
     session::{Parameter, ParmType},
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use super::{
+    param_expression::{self, ParamExpressionError},
+    param_menu::{self, MenuChoice},
+    ramp::RampKey,
+};
+
 #[derive(Debug, Error, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum AssetParamError {
     #[error("Failed to get current parameter value")]
     GetCurrent,
+    #[error("Failed to get parameter tags")]
+    GetTags,
+    #[error("Failed to get parameter expressions")]
+    GetExpressions(ParamExpressionError),
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum ParamValue {
-    Int { current: Vec<i32> },
-    Menu { choices: Vec<String>, current: i32 },
-    Toggle { current: bool },
-    Float { current: Vec<f32> },
-    String { current: Vec<String> },
+    Int {
+        current: Vec<i32>,
+    },
+    /// An int or string parameter with a menu. Writes select a choice by
+    /// `current`, or by `token` if `current` isn't set.
+    Menu {
+        /// Ignored when writing a value.
+        #[serde(default)]
+        choices: Vec<MenuChoice>,
+        /// Index of the selected choice. Not set if a string menu's value
+        /// isn't one of its choices.
+        #[serde(default)]
+        current: Option<i32>,
+        /// Token of the selected choice, or the value of a string menu.
+        #[serde(default)]
+        token: Option<String>,
+    },
+    Toggle {
+        current: bool,
+    },
+    Float {
+        current: Vec<f32>,
+    },
+    String {
+        current: Vec<String>,
+    },
+    /// Keys of a float or color ramp, ordered by position.
+    Ramp {
+        keys: Vec<RampKey>,
+    },
     NoDefault,
 }
 
@@ -259,6 +296,12 @@ pub struct SerializableParamInfo {
     pub input_node_type: NodeType,
     pub input_node_flag: NodeFlags,
     pub is_child_of_multi_param: bool,
+    /// Number of the multiparm instance this parameter belongs to.
+    pub instance_num: i32,
+    /// Number of instances, if this parameter is a multiparm.
+    pub instance_count: i32,
+    /// Number of the first instance, if this parameter is a multiparm.
+    pub instance_start_offset: i32,
     pub ramp_type: RampType,
     pub name: Option<String>,
     pub label: Option<String>,
@@ -310,6 +353,9 @@ impl From<&ParmInfo> for SerializableParamInfo {
             input_node_type: value.input_node_type().into(),
             input_node_flag: value.input_node_flag().into(),
             is_child_of_multi_param: value.is_child_of_multi_parm(),
+            instance_num: value.instance_num(),
+            instance_count: value.instance_count(),
+            instance_start_offset: value.instance_start_offset(),
             ramp_type: value.ramp_type().into(),
             name: value.name().ok(),
             label: value.label().ok(),
@@ -324,6 +370,26 @@ impl From<&ParmInfo> for SerializableParamInfo {
 pub struct SerializableParameter {
     pub current_value: ParamValue,
     pub info: SerializableParamInfo,
+    /// Raw parameter tags, such as conditional UI and editor hints. There are
+    /// `info.tag_count` of them.
+    pub tags: BTreeMap<String, String>,
+    /// Expression of each component, if it is driven by one. Empty for
+    /// parameters that can't have expressions.
+    pub expressions: Vec<Option<String>>,
+}
+
+fn read_tags(param: &Parameter) -> Result<BTreeMap<String, String>, AssetParamError> {
+    (0..param.info().tag_count())
+        .map(|index| {
+            let name = param
+                .get_tag_name(index)
+                .map_err(|_| AssetParamError::GetTags)?;
+            let value = param
+                .get_tag_value(&name)
+                .map_err(|_| AssetParamError::GetTags)?;
+            Ok((name, value))
+        })
+        .collect()
 }
 
 impl TryFrom<Parameter> for SerializableParameter {
@@ -331,6 +397,13 @@ impl TryFrom<Parameter> for SerializableParameter {
 
     fn try_from(param: Parameter) -> Result<Self, Self::Error> {
         let param_type = param.info().parm_type();
+        // A menu that can't be read, for example because its script fails,
+        // shouldn't hide the parameter or the rest of the asset's parameters.
+        let choices = param_menu::read_choices(&param).unwrap_or_else(|err| {
+            let name = param.info().name().unwrap_or_default();
+            log::warn!("Failed to read the menu of parameter {name}: {err}");
+            None
+        });
         let current_value = match &param {
             Parameter::Button(_) => ParamValue::NoDefault,
             Parameter::Float(param) => ParamValue::Float {
@@ -340,29 +413,47 @@ impl TryFrom<Parameter> for SerializableParameter {
                 if param_type == ParmType::Toggle {
                     let current = param.get(0).map_err(|_| AssetParamError::GetCurrent)? > 0;
                     ParamValue::Toggle { current }
-                } else if let Ok(Some(menu)) = param.menu_items() {
-                    let choices: Vec<String> = menu
-                        .into_iter()
-                        .map(|choice| choice.label().unwrap())
-                        .collect();
+                } else if let Some(choices) = choices {
                     let current = param.get(0).map_err(|_| AssetParamError::GetCurrent)?;
+                    let token = usize::try_from(current)
+                        .ok()
+                        .and_then(|index| choices.get(index))
+                        .map(|choice| choice.value.clone());
 
-                    ParamValue::Menu { choices, current }
+                    ParamValue::Menu {
+                        choices,
+                        current: Some(current),
+                        token,
+                    }
                 } else {
                     ParamValue::Int {
                         current: param.get_array().map_err(|_| AssetParamError::GetCurrent)?,
                     }
                 }
             }
-            Parameter::String(param) => ParamValue::String {
-                current: param.get_array().map_err(|_| AssetParamError::GetCurrent)?,
-            },
+            Parameter::String(param) => {
+                let current = param.get_array().map_err(|_| AssetParamError::GetCurrent)?;
+                match (choices, current.first()) {
+                    (Some(choices), Some(value)) => ParamValue::Menu {
+                        current: choices
+                            .iter()
+                            .position(|choice| &choice.value == value)
+                            .map(|index| index as i32),
+                        token: Some(value.clone()),
+                        choices,
+                    },
+                    _ => ParamValue::String { current },
+                }
+            }
             Parameter::Other(_) => ParamValue::NoDefault,
         };
 
         Ok(Self {
             current_value,
             info: param.info().into(),
+            tags: read_tags(&param)?,
+            expressions: param_expression::read_expressions(&param)
+                .map_err(AssetParamError::GetExpressions)?,
         })
     }
 }
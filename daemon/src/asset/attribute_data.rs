@@ -0,0 +1,297 @@
+/// This module extracts arbitrary, user-requested attributes from cooked asset
+/// geometry so they can be passed through to the client alongside the mesh.
+
+use hapi_rs::{
+    attribute::{NumericAttr, StringAttr},
+    geometry::PartInfo,
+    node::Geometry,
+    session::{AttributeOwner, StorageType},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Error, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttributeDataError {
+    #[error("Failed to get attribute {0}")]
+    GetAttribute(String),
+    #[error("Attribute {0} does not exist")]
+    MissingAttribute(String),
+    #[error("Attribute {0} has an unsupported storage type")]
+    UnsupportedStorage(String),
+    #[error("Failed to read values of attribute {0}")]
+    ReadAttribute(String),
+}
+
+type Result<T> = std::result::Result<T, AttributeDataError>;
+
+/// Allow-list of attributes to pass through, grouped by the element that owns
+/// them.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AttributeFilter {
+    pub point: Vec<String>,
+    pub vertex: Vec<String>,
+    pub prim: Vec<String>,
+    pub detail: Vec<String>,
+}
+
+impl AttributeFilter {
+    pub fn is_empty(&self) -> bool {
+        self.point.is_empty()
+            && self.vertex.is_empty()
+            && self.prim.is_empty()
+            && self.detail.is_empty()
+    }
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AttributeOwnerKind {
+    Point,
+    Vertex,
+    Prim,
+    Detail,
+}
+
+impl From<AttributeOwnerKind> for AttributeOwner {
+    fn from(value: AttributeOwnerKind) -> Self {
+        match value {
+            AttributeOwnerKind::Point => AttributeOwner::Point,
+            AttributeOwnerKind::Vertex => AttributeOwner::Vertex,
+            AttributeOwnerKind::Prim => AttributeOwner::Prim,
+            AttributeOwnerKind::Detail => AttributeOwner::Detail,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "storage", content = "values")]
+pub enum AttributeValues {
+    Int(Vec<i32>),
+    Float(Vec<f32>),
+    String(Vec<String>),
+}
+
+/// A single passed-through attribute.
+///
+/// Values are flattened tuples. Point and vertex attributes hold one tuple per
+/// emitted triangle corner (three per triangle, in the same order as the mesh
+/// `vertexArray`), primitive attributes hold one tuple per emitted triangle,
+/// and detail attributes hold a single tuple.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributeData {
+    pub name: String,
+    pub owner: AttributeOwnerKind,
+    pub tuple_size: i32,
+    #[serde(flatten)]
+    pub values: AttributeValues,
+}
+
+/// Raw values of an attribute, indexed by element number in Houdini.
+pub(crate) struct RawAttribute {
+    pub tuple_size: usize,
+    pub values: AttributeValues,
+}
+
+/// Reads the raw values of an attribute, converting supported storage types to
+/// `int`, `float` or `string`. Returns `None` if the attribute does not exist.
+pub(crate) fn read_raw_attribute(
+    geo: &Geometry,
+    part_id: i32,
+    owner: AttributeOwner,
+    name: &str,
+) -> Result<Option<RawAttribute>> {
+    let attr = geo
+        .get_attribute(part_id, owner, name)
+        .map_err(|_| AttributeDataError::GetAttribute(name.to_owned()))?;
+    let Some(attr) = attr else {
+        return Ok(None);
+    };
+
+    let tuple_size = attr.info().tuple_size() as usize;
+    let read_err = |_| AttributeDataError::ReadAttribute(name.to_owned());
+
+    let values = match attr.storage() {
+        StorageType::Int => AttributeValues::Int(
+            attr.downcast::<NumericAttr<i32>>()
+                .expect("int attribute is NumericAttribute")
+                .get(part_id)
+                .map_err(read_err)?,
+        ),
+        StorageType::Int64 => AttributeValues::Int(
+            attr.downcast::<NumericAttr<i64>>()
+                .expect("int64 attribute is NumericAttribute")
+                .get(part_id)
+                .map_err(read_err)?
+                .into_iter()
+                .map(|v| v as i32)
+                .collect(),
+        ),
+        StorageType::Float => AttributeValues::Float(
+            attr.downcast::<NumericAttr<f32>>()
+                .expect("float attribute is NumericAttribute")
+                .get(part_id)
+                .map_err(read_err)?,
+        ),
+        StorageType::Float64 => AttributeValues::Float(
+            attr.downcast::<NumericAttr<f64>>()
+                .expect("float64 attribute is NumericAttribute")
+                .get(part_id)
+                .map_err(read_err)?
+                .into_iter()
+                .map(|v| v as f32)
+                .collect(),
+        ),
+        StorageType::String => AttributeValues::String(
+            attr.downcast::<StringAttr>()
+                .expect("string attribute is StringAttribute")
+                .get(part_id)
+                .map_err(read_err)?
+                .into_iter()
+                .collect(),
+        ),
+        _ => return Err(AttributeDataError::UnsupportedStorage(name.to_owned())),
+    };
+
+    Ok(Some(RawAttribute { tuple_size, values }))
+}
+
+impl RawAttribute {
+    /// Builds a new set of values by picking the tuple at each element index.
+    pub fn gather(&self, indices: &[usize]) -> AttributeValues {
+        fn pick<T: Clone>(values: &[T], tuple_size: usize, indices: &[usize]) -> Vec<T> {
+            let mut out = Vec::with_capacity(indices.len() * tuple_size);
+            for &index in indices {
+                out.extend_from_slice(&values[index * tuple_size..(index + 1) * tuple_size]);
+            }
+            out
+        }
+
+        match &self.values {
+            AttributeValues::Int(v) => AttributeValues::Int(pick(v, self.tuple_size, indices)),
+            AttributeValues::Float(v) => AttributeValues::Float(pick(v, self.tuple_size, indices)),
+            AttributeValues::String(v) => {
+                AttributeValues::String(pick(v, self.tuple_size, indices))
+            }
+        }
+    }
+}
+
+/// Element indices of every emitted triangle, used to align attribute values
+/// with the triangulated mesh.
+pub(crate) struct TriangleIndices {
+    /// Houdini point index of every emitted triangle corner.
+    pub points: Vec<usize>,
+    /// Houdini vertex index of every emitted triangle corner.
+    pub vertices: Vec<usize>,
+    /// Houdini primitive index of every emitted triangle.
+    pub prims: Vec<usize>,
+}
+
+impl TriangleIndices {
+    /// Fan-triangulates every face, matching the triangulation used by
+    /// `MeshData`. If `prim_mask` is given, only primitives whose entry is
+    /// `true` are included.
+    pub fn new(face_counts: &[i32], vertex_list: &[i32], prim_mask: Option<&[bool]>) -> Self {
+        let mut triangles = Self::empty();
+
+        let mut offset = 0;
+        for (prim, &vertex_count_per_face) in face_counts.iter().enumerate() {
+            if prim_mask.map_or(true, |mask| mask[prim]) {
+                triangles.push_prim(prim, offset, vertex_count_per_face, vertex_list);
+            }
+            offset += vertex_count_per_face as usize;
+        }
+
+        triangles
+    }
+
+    /// Fan-triangulates the given primitives, in order. `prim_offsets` holds
+    /// the index of each primitive's first vertex.
+    pub fn from_prims(
+        face_counts: &[i32],
+        vertex_list: &[i32],
+        prim_offsets: &[usize],
+        prims: &[usize],
+    ) -> Self {
+        let mut triangles = Self::empty();
+        for &prim in prims {
+            triangles.push_prim(prim, prim_offsets[prim], face_counts[prim], vertex_list);
+        }
+        triangles
+    }
+
+    fn empty() -> Self {
+        Self {
+            points: Vec::new(),
+            vertices: Vec::new(),
+            prims: Vec::new(),
+        }
+    }
+
+    fn push_prim(&mut self, prim: usize, offset: usize, vertex_count: i32, vertex_list: &[i32]) {
+        let num_triangles = (vertex_count - 2).max(0) as usize;
+        for i in 0..num_triangles {
+            for off in [offset, offset + i + 1, offset + i + 2] {
+                self.vertices.push(off);
+                self.points.push(vertex_list[off] as usize);
+            }
+            self.prims.push(prim);
+        }
+    }
+}
+
+/// Every attribute in an allow-list, read once so meshes built from parts of
+/// the same geometry don't read them again.
+pub(crate) struct FilteredAttributes {
+    attributes: Vec<(String, AttributeOwnerKind, RawAttribute)>,
+}
+
+impl FilteredAttributes {
+    /// Reads every attribute in the allow-list.
+    pub fn read(geo: &Geometry, partition: &PartInfo, filter: &AttributeFilter) -> Result<Self> {
+        let part_id = partition.part_id();
+        let mut attributes = Vec::new();
+
+        let owners = [
+            (AttributeOwnerKind::Point, &filter.point),
+            (AttributeOwnerKind::Vertex, &filter.vertex),
+            (AttributeOwnerKind::Prim, &filter.prim),
+            (AttributeOwnerKind::Detail, &filter.detail),
+        ];
+
+        for (owner, names) in owners {
+            for name in names {
+                let raw = read_raw_attribute(geo, part_id, owner.into(), name)?
+                    .ok_or_else(|| AttributeDataError::MissingAttribute(name.to_owned()))?;
+                attributes.push((name.to_owned(), owner, raw));
+            }
+        }
+
+        Ok(Self { attributes })
+    }
+
+    /// Returns the attributes aligned with the emitted triangles.
+    pub fn align(&self, triangles: &TriangleIndices) -> Vec<AttributeData> {
+        self.attributes
+            .iter()
+            .map(|(name, owner, raw)| {
+                let values = match owner {
+                    AttributeOwnerKind::Point => raw.gather(&triangles.points),
+                    AttributeOwnerKind::Vertex => raw.gather(&triangles.vertices),
+                    AttributeOwnerKind::Prim => raw.gather(&triangles.prims),
+                    AttributeOwnerKind::Detail => raw.gather(&[0]),
+                };
+
+                AttributeData {
+                    name: name.clone(),
+                    owner: *owner,
+                    tuple_size: raw.tuple_size as i32,
+                    values,
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,223 @@
+/// This module computes convex collision hulls for cooked geometry, so
+/// procedural props can use cheap and accurate collision in Roblox.
+///
+/// If the geometry has a `collision` primitive group, it is treated as a
+/// proxy authored by the HDA: every connected piece of the proxy becomes one
+/// hull, and the proxy is left out of the render mesh. Otherwise the cooked
+/// mesh is decomposed into approximately convex parts with V-HACD.
+
+use std::{collections::HashMap, time::Instant};
+
+use glam::Vec3;
+use hapi_rs::{geometry::GroupType, node::Geometry};
+use parry3d::{
+    math::Point,
+    transformation::{
+        try_convex_hull,
+        vhacd::{VHACDParameters, VHACD},
+    },
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{attribute_data::TriangleIndices, mesh_data::GeometrySource};
+
+const COLLISION_GROUP: &str = "collision";
+
+#[derive(Debug, Error, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollisionError {
+    #[error("Failed to get primitive groups")]
+    GetGroups,
+    #[error("Geometry has no triangles to build collision from")]
+    NoTriangles,
+    #[error("Failed to build a convex hull from collision piece {0}")]
+    BuildHull(usize),
+}
+
+type Result<T> = std::result::Result<T, CollisionError>;
+
+/// Client-provided settings for the collision pass.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CollisionOptions {
+    /// Maximum number of hulls to generate.
+    pub max_convex_hulls: u32,
+    /// Maximum concavity of each hull, in the range `[0, 1]`. Lower values
+    /// produce more, tighter hulls.
+    pub concavity: f32,
+    /// Voxel resolution used for the decomposition.
+    pub resolution: u32,
+}
+
+impl Default for CollisionOptions {
+    fn default() -> Self {
+        Self {
+            max_convex_hulls: 16,
+            concavity: 0.01,
+            resolution: 64,
+        }
+    }
+}
+
+/// A single convex hull as an indexed triangle mesh.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConvexHull {
+    pub positions: Vec<Vec3>,
+    pub triangles: Vec<[u32; 3]>,
+}
+
+impl From<(Vec<Point<f32>>, Vec<[u32; 3]>)> for ConvexHull {
+    fn from((points, triangles): (Vec<Point<f32>>, Vec<[u32; 3]>)) -> Self {
+        Self {
+            positions: points.iter().map(|p| Vec3::new(p.x, p.y, p.z)).collect(),
+            triangles,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollisionData {
+    pub hulls: Vec<ConvexHull>,
+    /// Whether the hulls were built from a `collision` group in the HDA.
+    pub from_proxy: bool,
+    pub processing_time: f64,
+}
+
+/// Returns which primitives are in the `collision` group, if it exists.
+pub(crate) fn collision_proxy_mask(
+    geo: &Geometry,
+    source: &GeometrySource,
+) -> Result<Option<Vec<bool>>> {
+    let has_group = geo
+        .get_group_names(GroupType::Prim)
+        .map_err(|_| CollisionError::GetGroups)?
+        .into_iter()
+        .any(|name| name == COLLISION_GROUP);
+    if !has_group {
+        return Ok(None);
+    }
+
+    let mask: Vec<bool> = geo
+        .get_group_membership(&source.partition, GroupType::Prim, COLLISION_GROUP)
+        .map_err(|_| CollisionError::GetGroups)?
+        .into_iter()
+        .map(|member| member != 0)
+        .collect();
+
+    Ok(Some(mask).filter(|mask| mask.iter().any(|member| *member)))
+}
+
+fn point_at(positions: &[f32], point: usize) -> Point<f32> {
+    Point::new(
+        positions[point * 3],
+        positions[point * 3 + 1],
+        positions[point * 3 + 2],
+    )
+}
+
+/// Groups the triangles of a mesh into pieces connected by shared points.
+/// Returns the points of every piece.
+fn connected_pieces(triangles: &TriangleIndices, point_count: usize) -> Vec<Vec<usize>> {
+    fn find(parents: &mut [usize], mut point: usize) -> usize {
+        while parents[point] != point {
+            parents[point] = parents[parents[point]];
+            point = parents[point];
+        }
+        point
+    }
+
+    let mut parents: Vec<usize> = (0..point_count).collect();
+    for corners in triangles.points.chunks_exact(3) {
+        let a = find(&mut parents, corners[0]);
+        for &corner in &corners[1..] {
+            let b = find(&mut parents, corner);
+            parents[b] = a;
+        }
+    }
+
+    let mut pieces: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut order = Vec::new();
+    let mut seen = vec![false; point_count];
+    for &point in &triangles.points {
+        if std::mem::replace(&mut seen[point], true) {
+            continue;
+        }
+
+        let root = find(&mut parents, point);
+        pieces
+            .entry(root)
+            .or_insert_with(|| {
+                order.push(root);
+                Vec::new()
+            })
+            .push(point);
+    }
+
+    order
+        .into_iter()
+        .filter_map(|root| pieces.remove(&root))
+        .collect()
+}
+
+impl CollisionData {
+    pub(crate) fn from_source(
+        source: &GeometrySource,
+        proxy_mask: Option<&[bool]>,
+        options: &CollisionOptions,
+    ) -> Result<Self> {
+        let start = Instant::now();
+
+        let triangles = TriangleIndices::new(&source.face_counts, &source.vertex_list, proxy_mask);
+        if triangles.points.is_empty() {
+            return Err(CollisionError::NoTriangles);
+        }
+
+        let hulls = if proxy_mask.is_some() {
+            connected_pieces(&triangles, source.positions.len() / 3)
+                .into_iter()
+                .enumerate()
+                .map(|(index, piece)| {
+                    let points: Vec<Point<f32>> = piece
+                        .into_iter()
+                        .map(|point| point_at(&source.positions, point))
+                        .collect();
+                    try_convex_hull(&points)
+                        .map(ConvexHull::from)
+                        .map_err(|_| CollisionError::BuildHull(index))
+                })
+                .collect::<Result<_>>()?
+        } else {
+            let points: Vec<Point<f32>> = (0..source.positions.len() / 3)
+                .map(|point| point_at(&source.positions, point))
+                .collect();
+            let indices: Vec<[u32; 3]> = triangles
+                .points
+                .chunks_exact(3)
+                .map(|corners| [corners[0] as u32, corners[1] as u32, corners[2] as u32])
+                .collect();
+
+            let parameters = VHACDParameters {
+                max_convex_hulls: options.max_convex_hulls.max(1),
+                concavity: options.concavity.clamp(0.0, 1.0),
+                resolution: options.resolution.max(1),
+                ..Default::default()
+            };
+            let decomposition = VHACD::decompose(&parameters, &points, &indices, false);
+
+            decomposition
+                .compute_convex_hulls(parameters.convex_hull_downsampling)
+                .into_iter()
+                .map(ConvexHull::from)
+                .collect()
+        };
+
+        Ok(Self {
+            hulls,
+            from_proxy: proxy_mask.is_some(),
+            processing_time: Instant::now().duration_since(start).as_secs_f64(),
+        })
+    }
+}
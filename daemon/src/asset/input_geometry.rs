@@ -0,0 +1,445 @@
+/// This module converts geometry sent by the plugin into Houdini geometry, so
+/// it can be used as the input of an asset.
+
+use glam::{Mat3, Vec3};
+use hapi_rs::{
+    attribute::AttributeInfo,
+    geometry::{CurveInfo, CurveType, PartInfo, PartType},
+    node::Geometry,
+    session::{AttributeOwner, StorageType},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::attribute_data::AttributeValues;
+
+/// Size of a stud in Houdini units (meters). This is the scale of an asset
+/// until its input geometry is sent with another one.
+pub const METERS_PER_STUD: f32 = 0.28;
+
+#[derive(Debug, Error, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputGeometryError {
+    #[error("Triangle {0} references a vertex that does not exist")]
+    InvalidTriangle(usize),
+    #[error("Failed to set input part info")]
+    SetPartInfo,
+    #[error("Failed to set input attribute {0}")]
+    SetAttribute(&'static str),
+    #[error("Failed to set input topology")]
+    SetTopology,
+    #[error("A curve needs at least {0} points")]
+    NotEnoughCurvePoints(usize),
+    #[error("Curve order must be at least 2, got {0}")]
+    InvalidCurveOrder(i32),
+    #[error("A Bezier curve of order {order} can't be built from {points} points")]
+    BezierPointCount { points: usize, order: i32 },
+    #[error("Attribute {0} does not have one value per point")]
+    AttributeSizeMismatch(String),
+    #[error("Failed to set input attribute {0}")]
+    SetCustomAttribute(String),
+    #[error("Failed to set input curve info")]
+    SetCurveInfo,
+    #[error("Failed to commit input geometry")]
+    Commit,
+}
+
+type Result<T> = std::result::Result<T, InputGeometryError>;
+
+/// A Roblox `Part` approximated as a box.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartBox {
+    pub size: Vec3,
+    /// The part's `CFrame`, as the 12 components returned by
+    /// `CFrame:GetComponents()`.
+    pub cframe: [f32; 12],
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+pub enum CurveKind {
+    Polygon,
+    Nurbs,
+    Bezier,
+}
+
+impl CurveKind {
+    fn default_order(self) -> i32 {
+        match self {
+            Self::Polygon => 2,
+            Self::Nurbs | Self::Bezier => 4,
+        }
+    }
+}
+
+impl From<CurveKind> for CurveType {
+    fn from(value: CurveKind) -> Self {
+        match value {
+            CurveKind::Polygon => CurveType::Linear,
+            CurveKind::Nurbs => CurveType::Nurbs,
+            CurveKind::Bezier => CurveType::Bezier,
+        }
+    }
+}
+
+/// A per-point attribute sent along with input geometry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InputAttribute {
+    pub name: String,
+    pub tuple_size: i32,
+    #[serde(flatten)]
+    pub values: AttributeValues,
+}
+
+/// Geometry sent by the plugin. All positions are in studs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum InputGeometry {
+    /// A triangle list, for example read from an `EditableMesh`.
+    #[serde(rename_all = "camelCase")]
+    Mesh {
+        positions: Vec<Vec3>,
+        triangles: Vec<[u32; 3]>,
+    },
+    /// A set of parts, each converted to a box.
+    #[serde(rename_all = "camelCase")]
+    Parts { parts: Vec<PartBox> },
+    /// An ordered list of points, for example from `Attachment`s or `Path2D`
+    /// control points.
+    #[serde(rename_all = "camelCase")]
+    Curve {
+        points: Vec<Vec3>,
+        curve_type: CurveKind,
+        #[serde(default)]
+        closed: bool,
+        /// Curve order. Defaults to 2 for polygons and 4 otherwise.
+        #[serde(default)]
+        order: Option<i32>,
+        #[serde(default)]
+        attributes: Vec<InputAttribute>,
+    },
+}
+
+/// Polygon mesh ready to be sent to HAPI.
+struct PolygonMesh {
+    positions: Vec<f32>,
+    face_counts: Vec<i32>,
+    vertex_list: Vec<i32>,
+}
+
+/// Corners of a unit box, and the quads connecting them.
+const BOX_CORNERS: [[f32; 3]; 8] = [
+    [-1.0, -1.0, -1.0],
+    [1.0, -1.0, -1.0],
+    [1.0, 1.0, -1.0],
+    [-1.0, 1.0, -1.0],
+    [-1.0, -1.0, 1.0],
+    [1.0, -1.0, 1.0],
+    [1.0, 1.0, 1.0],
+    [-1.0, 1.0, 1.0],
+];
+const BOX_FACES: [[i32; 4]; 6] = [
+    [0, 1, 2, 3],
+    [5, 4, 7, 6],
+    [4, 0, 3, 7],
+    [1, 5, 6, 2],
+    [3, 2, 6, 7],
+    [4, 5, 1, 0],
+];
+
+impl InputGeometry {
+    /// Returns `None` for geometry that isn't a polygon mesh.
+    fn to_polygon_mesh(&self, units_per_stud: f32) -> Result<Option<PolygonMesh>> {
+        let mesh = match self {
+            Self::Mesh {
+                positions,
+                triangles,
+            } => {
+                let mut vertex_list = Vec::with_capacity(triangles.len() * 3);
+                for (index, triangle) in triangles.iter().enumerate() {
+                    if triangle.iter().any(|v| *v as usize >= positions.len()) {
+                        return Err(InputGeometryError::InvalidTriangle(index));
+                    }
+                    vertex_list.extend(triangle.iter().map(|v| *v as i32));
+                }
+
+                PolygonMesh {
+                    positions: positions
+                        .iter()
+                        .flat_map(|p| (*p * units_per_stud).to_array())
+                        .collect(),
+                    face_counts: vec![3; triangles.len()],
+                    vertex_list,
+                }
+            }
+            Self::Parts { parts } => {
+                let mut mesh = PolygonMesh {
+                    positions: Vec::with_capacity(parts.len() * BOX_CORNERS.len() * 3),
+                    face_counts: Vec::with_capacity(parts.len() * BOX_FACES.len()),
+                    vertex_list: Vec::with_capacity(parts.len() * BOX_FACES.len() * 4),
+                };
+
+                for (index, part) in parts.iter().enumerate() {
+                    let [x, y, z, r00, r01, r02, r10, r11, r12, r20, r21, r22] = part.cframe;
+                    let rotation = Mat3::from_cols(
+                        Vec3::new(r00, r10, r20),
+                        Vec3::new(r01, r11, r21),
+                        Vec3::new(r02, r12, r22),
+                    );
+                    let translation = Vec3::new(x, y, z);
+                    let half_size = part.size * 0.5;
+
+                    for corner in BOX_CORNERS {
+                        let local = Vec3::from_array(corner) * half_size;
+                        let world = (rotation * local + translation) * units_per_stud;
+                        mesh.positions.extend(world.to_array());
+                    }
+
+                    let offset = (index * BOX_CORNERS.len()) as i32;
+                    for face in BOX_FACES {
+                        mesh.face_counts.push(face.len() as i32);
+                        mesh.vertex_list.extend(face.iter().map(|v| v + offset));
+                    }
+                }
+
+                mesh
+            }
+            Self::Curve { .. } => return Ok(None),
+        };
+
+        Ok(Some(mesh))
+    }
+
+    /// Replaces the contents of an input node's geometry with this geometry.
+    pub(crate) fn upload(&self, geo: &Geometry, units_per_stud: f32) -> Result<()> {
+        if let Some(mesh) = self.to_polygon_mesh(units_per_stud)? {
+            upload_polygon_mesh(geo, &mesh)?;
+        } else if let Self::Curve {
+            points,
+            curve_type,
+            closed,
+            order,
+            attributes,
+        } = self
+        {
+            let order = order.unwrap_or_else(|| curve_type.default_order());
+            validate_curve(points.len(), *curve_type, *closed, order)?;
+
+            let positions: Vec<f32> = points
+                .iter()
+                .flat_map(|p| (*p * units_per_stud).to_array())
+                .collect();
+            upload_curve(geo, &positions, *curve_type, *closed, order, attributes)?;
+        }
+
+        geo.commit().map_err(|_| InputGeometryError::Commit)?;
+
+        Ok(())
+    }
+}
+
+fn set_positions(geo: &Geometry, part_id: i32, positions: &[f32]) -> Result<()> {
+    let position_info = AttributeInfo::default()
+        .with_count((positions.len() / 3) as i32)
+        .with_tuple_size(3)
+        .with_owner(AttributeOwner::Point)
+        .with_storage(StorageType::Float);
+    geo.add_numeric_attribute::<f32>("P", part_id, position_info)
+        .map_err(|_| InputGeometryError::SetAttribute("P"))?
+        .set(part_id, positions)
+        .map_err(|_| InputGeometryError::SetAttribute("P"))?;
+
+    Ok(())
+}
+
+fn set_point_attribute(
+    geo: &Geometry,
+    part_id: i32,
+    point_count: usize,
+    attribute: &InputAttribute,
+) -> Result<()> {
+    let value_count = match &attribute.values {
+        AttributeValues::Int(values) => values.len(),
+        AttributeValues::Float(values) => values.len(),
+        AttributeValues::String(values) => values.len(),
+    };
+    if attribute.tuple_size < 1 || value_count != point_count * attribute.tuple_size as usize {
+        return Err(InputGeometryError::AttributeSizeMismatch(
+            attribute.name.clone(),
+        ));
+    }
+
+    let info = AttributeInfo::default()
+        .with_count(point_count as i32)
+        .with_tuple_size(attribute.tuple_size)
+        .with_owner(AttributeOwner::Point);
+    let set_err = |_| InputGeometryError::SetCustomAttribute(attribute.name.clone());
+
+    match &attribute.values {
+        AttributeValues::Int(values) => geo
+            .add_numeric_attribute::<i32>(
+                &attribute.name,
+                part_id,
+                info.with_storage(StorageType::Int),
+            )
+            .map_err(set_err)?
+            .set(part_id, values)
+            .map_err(set_err)?,
+        AttributeValues::Float(values) => geo
+            .add_numeric_attribute::<f32>(
+                &attribute.name,
+                part_id,
+                info.with_storage(StorageType::Float),
+            )
+            .map_err(set_err)?
+            .set(part_id, values)
+            .map_err(set_err)?,
+        AttributeValues::String(values) => {
+            let values: Vec<&str> = values.iter().map(String::as_str).collect();
+            geo.add_string_attribute(
+                &attribute.name,
+                part_id,
+                info.with_storage(StorageType::String),
+            )
+            .map_err(set_err)?
+            .set(part_id, &values)
+            .map_err(set_err)?
+        }
+    }
+
+    Ok(())
+}
+
+fn upload_polygon_mesh(geo: &Geometry, mesh: &PolygonMesh) -> Result<()> {
+    let part_info = PartInfo::default()
+        .with_part_type(PartType::Mesh)
+        .with_face_count(mesh.face_counts.len() as i32)
+        .with_point_count((mesh.positions.len() / 3) as i32)
+        .with_vertex_count(mesh.vertex_list.len() as i32);
+    geo.set_part_info(&part_info)
+        .map_err(|_| InputGeometryError::SetPartInfo)?;
+    let part_id = part_info.part_id();
+
+    set_positions(geo, part_id, &mesh.positions)?;
+
+    geo.set_face_counts(part_id, &mesh.face_counts)
+        .map_err(|_| InputGeometryError::SetTopology)?;
+    geo.set_vertex_list(part_id, &mesh.vertex_list)
+        .map_err(|_| InputGeometryError::SetTopology)?;
+
+    Ok(())
+}
+
+/// Checks that a curve of `order` can be built from `point_count` points.
+/// Bezier curves are made of segments of `order - 1` points, sharing their
+/// end points, so open curves need `(n - 1) % (order - 1) == 0` and closed
+/// curves `n % (order - 1) == 0`.
+fn validate_curve(
+    point_count: usize,
+    curve_type: CurveKind,
+    closed: bool,
+    order: i32,
+) -> Result<()> {
+    if order < 2 {
+        return Err(InputGeometryError::InvalidCurveOrder(order));
+    }
+    let order_points = order as usize;
+    if point_count < order_points {
+        return Err(InputGeometryError::NotEnoughCurvePoints(order_points));
+    }
+
+    if matches!(curve_type, CurveKind::Bezier) {
+        let segment_points = order_points - 1;
+        let shared_points = if closed { point_count } else { point_count - 1 };
+        if shared_points % segment_points != 0 {
+            return Err(InputGeometryError::BezierPointCount {
+                points: point_count,
+                order,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+fn upload_curve(
+    geo: &Geometry,
+    positions: &[f32],
+    curve_type: CurveKind,
+    closed: bool,
+    order: i32,
+    attributes: &[InputAttribute],
+) -> Result<()> {
+    let point_count = positions.len() / 3;
+
+    let part_info = PartInfo::default()
+        .with_part_type(PartType::Curve)
+        .with_face_count(1)
+        .with_point_count(point_count as i32)
+        .with_vertex_count(point_count as i32);
+    geo.set_part_info(&part_info)
+        .map_err(|_| InputGeometryError::SetPartInfo)?;
+    let part_id = part_info.part_id();
+
+    let curve_info = CurveInfo::default()
+        .with_curve_type(curve_type.into())
+        .with_curve_count(1)
+        .with_vertex_count(point_count as i32)
+        .with_order(order)
+        .with_periodic(closed)
+        .with_rational(false)
+        .with_has_knots(false);
+    geo.set_curve_info(part_id, &curve_info)
+        .map_err(|_| InputGeometryError::SetCurveInfo)?;
+    geo.set_curve_counts(part_id, &[point_count as i32])
+        .map_err(|_| InputGeometryError::SetCurveInfo)?;
+    geo.set_curve_orders(part_id, &[order])
+        .map_err(|_| InputGeometryError::SetCurveInfo)?;
+
+    set_positions(geo, part_id, positions)?;
+
+    for attribute in attributes {
+        set_point_attribute(geo, part_id, point_count, attribute)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_orders_below_two() {
+        for order in [-1, 0, 1] {
+            assert!(matches!(
+                validate_curve(4, CurveKind::Nurbs, false, order),
+                Err(InputGeometryError::InvalidCurveOrder(o)) if o == order
+            ));
+        }
+    }
+
+    #[test]
+    fn needs_as_many_points_as_the_order() {
+        assert!(matches!(
+            validate_curve(3, CurveKind::Nurbs, false, 4),
+            Err(InputGeometryError::NotEnoughCurvePoints(4))
+        ));
+        assert!(validate_curve(4, CurveKind::Nurbs, false, 4).is_ok());
+    }
+
+    #[test]
+    fn bezier_points_fit_whole_segments() {
+        assert!(validate_curve(7, CurveKind::Bezier, false, 4).is_ok());
+        assert!(validate_curve(6, CurveKind::Bezier, true, 4).is_ok());
+        assert!(matches!(
+            validate_curve(6, CurveKind::Bezier, false, 4),
+            Err(InputGeometryError::BezierPointCount {
+                points: 6,
+                order: 4
+            })
+        ));
+        assert!(validate_curve(6, CurveKind::Nurbs, false, 4).is_ok());
+    }
+}
@@ -0,0 +1,397 @@
+/// This module interprets Roblox-specific attributes on cooked geometry to
+/// build a description of a whole Roblox instance hierarchy.
+///
+/// # Attribute convention
+///
+/// Primitives describe mesh-backed instances, and points describe
+/// instances without geometry (for example `Attachment`s):
+///
+/// - `rbx_name` (string): Name of the instance. Primitives sharing the same
+///   name and parent are merged into one instance with one mesh. Primitives
+///   with an empty name are ignored. Points are only considered when they
+///   also have a non-empty `rbx_class`.
+/// - `rbx_class` (string): Class of the instance. Defaults to `MeshPart` for
+///   primitives.
+/// - `rbx_parent` (string): Slash-separated path of the parent instance,
+///   relative to the root. Empty means the root. Parents that are not
+///   described by any element are created as `Model`s.
+/// - `rbx_tags` (string): Comma-separated list of CollectionService tags.
+/// - `rbx_prop_<Property>`: Value of the `<Property>` property. Floats with a
+///   tuple size of 3 are sent as `Color3` when the property name ends with
+///   `Color`, otherwise as `Vector3`. Property values are sent as-is, so
+///   lengths such as `Size` are in Houdini units, not studs.
+///
+/// Detail attributes `rbx_name`, `rbx_class`, `rbx_tags` and `rbx_prop_*`
+/// describe the root instance, which is a `Model` by default.
+
+use std::collections::{BTreeMap, HashMap};
+
+use glam::{Vec2, Vec3};
+use hapi_rs::{node::Geometry, session::AttributeOwner};
+use serde::Serialize;
+use thiserror::Error;
+
+use super::{
+    attribute_data::{read_raw_attribute, AttributeDataError, AttributeValues, RawAttribute},
+    mesh_data::{GeometrySource, MeshDataError},
+    AttributeFilter, MeshData,
+};
+
+const NAME_ATTRIBUTE: &str = "rbx_name";
+const CLASS_ATTRIBUTE: &str = "rbx_class";
+const PARENT_ATTRIBUTE: &str = "rbx_parent";
+const TAGS_ATTRIBUTE: &str = "rbx_tags";
+const PROPERTY_PREFIX: &str = "rbx_prop_";
+
+const DEFAULT_ROOT_CLASS: &str = "Model";
+const DEFAULT_ROOT_NAME: &str = "Root";
+const DEFAULT_PRIM_CLASS: &str = "MeshPart";
+const DEFAULT_PARENT_CLASS: &str = "Model";
+
+#[derive(Debug, Error, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstanceTreeError {
+    #[error("Failed to get attribute names")]
+    GetAttributeNames,
+    #[error("Failed to read instance attribute")]
+    ReadAttribute(AttributeDataError),
+    #[error("Attribute {0} must be a string attribute")]
+    ExpectedString(String),
+    #[error("Failed to build instance mesh")]
+    BuildMesh(MeshDataError),
+}
+
+type Result<T> = std::result::Result<T, InstanceTreeError>;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "value")]
+pub enum PropertyValue {
+    Number(f32),
+    Vector2(Vec2),
+    Vector3(Vec3),
+    Color3(Vec3),
+    String(String),
+    /// Tuples that don't map onto a Roblox type are sent as-is.
+    Tuple(Vec<f32>),
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceDescription {
+    pub name: String,
+    pub class_name: String,
+    pub properties: BTreeMap<String, PropertyValue>,
+    pub tags: Vec<String>,
+    /// Index into `InstanceTree::meshes` of the mesh backing this instance.
+    pub mesh: Option<usize>,
+    /// Position of the point that described this instance, in geometry space.
+    pub position: Option<Vec3>,
+    pub children: Vec<InstanceDescription>,
+}
+
+impl InstanceDescription {
+    fn new(name: String, class_name: String) -> Self {
+        Self {
+            name,
+            class_name,
+            properties: BTreeMap::new(),
+            tags: Vec::new(),
+            mesh: None,
+            position: None,
+            children: Vec::new(),
+        }
+    }
+}
+
+/// A serialized Roblox instance hierarchy, along with the meshes referenced by
+/// its instances.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceTree {
+    pub root: InstanceDescription,
+    pub meshes: Vec<MeshData>,
+}
+
+/// Attributes following the convention, read for a single owner.
+struct OwnerAttributes {
+    names: Option<Vec<String>>,
+    classes: Option<Vec<String>>,
+    parents: Option<Vec<String>>,
+    tags: Option<Vec<String>>,
+    properties: Vec<(String, RawAttribute)>,
+}
+
+impl OwnerAttributes {
+    fn read(geo: &Geometry, source: &GeometrySource, owner: AttributeOwner) -> Result<Self> {
+        let part_id = source.partition.part_id();
+
+        let read_strings = |name: &str| -> Result<Option<Vec<String>>> {
+            let raw = read_raw_attribute(geo, part_id, owner, name)
+                .map_err(InstanceTreeError::ReadAttribute)?;
+            match raw {
+                Some(RawAttribute {
+                    values: AttributeValues::String(values),
+                    ..
+                }) => Ok(Some(values)),
+                Some(_) => Err(InstanceTreeError::ExpectedString(name.to_owned())),
+                None => Ok(None),
+            }
+        };
+
+        let mut properties = Vec::new();
+        let attribute_names = geo
+            .get_attribute_names(owner, &source.partition)
+            .map_err(|_| InstanceTreeError::GetAttributeNames)?;
+        for attribute_name in attribute_names.into_iter() {
+            if let Some(property) = attribute_name.strip_prefix(PROPERTY_PREFIX) {
+                let raw = read_raw_attribute(geo, part_id, owner, &attribute_name)
+                    .map_err(InstanceTreeError::ReadAttribute)?;
+                if let Some(raw) = raw {
+                    properties.push((property.to_owned(), raw));
+                }
+            }
+        }
+
+        Ok(Self {
+            names: read_strings(NAME_ATTRIBUTE)?,
+            classes: read_strings(CLASS_ATTRIBUTE)?,
+            parents: read_strings(PARENT_ATTRIBUTE)?,
+            tags: read_strings(TAGS_ATTRIBUTE)?,
+            properties,
+        })
+    }
+
+    fn string_at(values: &Option<Vec<String>>, index: usize) -> Option<&str> {
+        values
+            .as_ref()
+            .and_then(|values| values.get(index))
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    }
+
+    /// Applies class, tags and properties of the element at `index` to an
+    /// instance.
+    fn apply(&self, index: usize, instance: &mut InstanceDescription) {
+        if let Some(class_name) = Self::string_at(&self.classes, index) {
+            instance.class_name = class_name.to_owned();
+        }
+
+        if let Some(tags) = Self::string_at(&self.tags, index) {
+            for tag in tags.split(',').map(str::trim).filter(|tag| !tag.is_empty()) {
+                if !instance.tags.iter().any(|existing| existing == tag) {
+                    instance.tags.push(tag.to_owned());
+                }
+            }
+        }
+
+        for (property, raw) in &self.properties {
+            if let Some(value) = property_value(property, raw, index) {
+                instance.properties.insert(property.clone(), value);
+            }
+        }
+    }
+}
+
+fn property_value(property: &str, raw: &RawAttribute, index: usize) -> Option<PropertyValue> {
+    let start = index * raw.tuple_size;
+    let end = start + raw.tuple_size;
+
+    let value = match &raw.values {
+        AttributeValues::String(values) => PropertyValue::String(values.get(start)?.clone()),
+        AttributeValues::Int(values) => {
+            let tuple: Vec<f32> = values.get(start..end)?.iter().map(|v| *v as f32).collect();
+            tuple_value(property, tuple)
+        }
+        AttributeValues::Float(values) => tuple_value(property, values.get(start..end)?.to_vec()),
+    };
+
+    Some(value)
+}
+
+fn tuple_value(property: &str, tuple: Vec<f32>) -> PropertyValue {
+    match tuple.len() {
+        1 => PropertyValue::Number(tuple[0]),
+        2 => PropertyValue::Vector2(Vec2::new(tuple[0], tuple[1])),
+        3 if property.ends_with("Color") => {
+            PropertyValue::Color3(Vec3::new(tuple[0], tuple[1], tuple[2]))
+        }
+        3 => PropertyValue::Vector3(Vec3::new(tuple[0], tuple[1], tuple[2])),
+        _ => PropertyValue::Tuple(tuple),
+    }
+}
+
+/// Joins a parent path and a name into the path of an instance.
+fn join_path(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{parent}/{name}")
+    }
+}
+
+/// A described instance before it is placed in the hierarchy.
+struct FlatInstance {
+    parent: String,
+    instance: InstanceDescription,
+    prims: Vec<usize>,
+}
+
+impl InstanceTree {
+    /// Builds the instance tree of already-read geometry. If `prim_mask` is
+    /// given, only primitives whose entry is `true` are placed in meshes.
+    pub(crate) fn from_source(
+        geo: &Geometry,
+        source: &GeometrySource,
+        prim_mask: Option<&[bool]>,
+        attribute_filter: &AttributeFilter,
+    ) -> Result<Self> {
+        let mut flat: HashMap<String, FlatInstance> = HashMap::new();
+        // Remember the order instances were first seen in, so the output is
+        // stable between cooks.
+        let mut order: Vec<String> = Vec::new();
+
+        let prim_attributes = OwnerAttributes::read(geo, source, AttributeOwner::Prim)?;
+        for prim in 0..source.prim_count() {
+            if prim_mask.map_or(false, |mask| !mask[prim]) {
+                continue;
+            }
+            let Some(name) = OwnerAttributes::string_at(&prim_attributes.names, prim) else {
+                continue;
+            };
+            let parent = OwnerAttributes::string_at(&prim_attributes.parents, prim)
+                .unwrap_or_default()
+                .trim_matches('/')
+                .to_owned();
+            let path = join_path(&parent, name);
+
+            let entry = flat.entry(path.clone()).or_insert_with(|| {
+                order.push(path);
+                FlatInstance {
+                    parent,
+                    instance: InstanceDescription::new(
+                        name.to_owned(),
+                        DEFAULT_PRIM_CLASS.to_owned(),
+                    ),
+                    prims: Vec::new(),
+                }
+            });
+
+            if entry.prims.is_empty() {
+                prim_attributes.apply(prim, &mut entry.instance);
+            }
+            entry.prims.push(prim);
+        }
+
+        let point_attributes = OwnerAttributes::read(geo, source, AttributeOwner::Point)?;
+        let point_count = source.positions.len() / 3;
+        for point in 0..point_count {
+            let Some(name) = OwnerAttributes::string_at(&point_attributes.names, point) else {
+                continue;
+            };
+            if OwnerAttributes::string_at(&point_attributes.classes, point).is_none() {
+                continue;
+            }
+            let parent = OwnerAttributes::string_at(&point_attributes.parents, point)
+                .unwrap_or_default()
+                .trim_matches('/')
+                .to_owned();
+            let path = join_path(&parent, name);
+
+            if flat.contains_key(&path) {
+                log::warn!("Instance {path} is described more than once, ignoring point {point}");
+                continue;
+            }
+
+            let mut instance =
+                InstanceDescription::new(name.to_owned(), DEFAULT_PARENT_CLASS.to_owned());
+            point_attributes.apply(point, &mut instance);
+            instance.position = Some(Vec3::new(
+                source.positions[point * 3],
+                source.positions[point * 3 + 1],
+                source.positions[point * 3 + 2],
+            ));
+
+            order.push(path.clone());
+            flat.insert(
+                path,
+                FlatInstance {
+                    parent,
+                    instance,
+                    prims: Vec::new(),
+                },
+            );
+        }
+
+        // Create any parents that aren't described by an element.
+        let mut index = 0;
+        while index < order.len() {
+            let parent = flat[&order[index]].parent.clone();
+            if !parent.is_empty() && !flat.contains_key(&parent) {
+                let (grandparent, name) = match parent.rsplit_once('/') {
+                    Some((grandparent, name)) => (grandparent.to_owned(), name.to_owned()),
+                    None => (String::new(), parent.clone()),
+                };
+
+                order.push(parent.clone());
+                flat.insert(
+                    parent,
+                    FlatInstance {
+                        parent: grandparent,
+                        instance: InstanceDescription::new(name, DEFAULT_PARENT_CLASS.to_owned()),
+                        prims: Vec::new(),
+                    },
+                );
+            }
+            index += 1;
+        }
+
+        // Build one mesh per instance with primitives, from the primitives
+        // grouped above. Requested attributes are read once for all of them.
+        let attributes = MeshData::read_attributes(geo, source, attribute_filter)
+            .map_err(InstanceTreeError::BuildMesh)?;
+        let mut meshes = Vec::new();
+        for path in &order {
+            let entry = flat.get_mut(path).expect("instance exists");
+            if entry.prims.is_empty() {
+                continue;
+            }
+
+            entry.instance.mesh = Some(meshes.len());
+            meshes.push(MeshData::from_prims(source, &entry.prims, &attributes));
+        }
+
+        let mut root =
+            InstanceDescription::new(DEFAULT_ROOT_NAME.to_owned(), DEFAULT_ROOT_CLASS.to_owned());
+        let detail_attributes = OwnerAttributes::read(geo, source, AttributeOwner::Detail)?;
+        if let Some(name) = OwnerAttributes::string_at(&detail_attributes.names, 0) {
+            root.name = name.to_owned();
+        }
+        detail_attributes.apply(0, &mut root);
+
+        // Attach children to their parents, deepest paths first so every
+        // instance is complete before it is moved into its parent. Siblings
+        // keep the order they were first seen in.
+        let mut order: Vec<(usize, String)> = order.into_iter().enumerate().collect();
+        order.sort_by_key(|(_, path)| std::cmp::Reverse(path.matches('/').count()));
+
+        let mut children: HashMap<String, Vec<(usize, InstanceDescription)>> = HashMap::new();
+        for (seen, path) in &order {
+            let mut entry = flat.remove(path).expect("instance exists");
+            if let Some(mut own_children) = children.remove(path) {
+                own_children.sort_by_key(|(seen, _)| *seen);
+                entry.instance.children = own_children.into_iter().map(|(_, c)| c).collect();
+            }
+            children
+                .entry(entry.parent)
+                .or_default()
+                .push((*seen, entry.instance));
+        }
+
+        if let Some(mut own_children) = children.remove("") {
+            own_children.sort_by_key(|(seen, _)| *seen);
+            root.children = own_children.into_iter().map(|(_, c)| c).collect();
+        }
+
+        Ok(Self { root, meshes })
+    }
+}
@@ -0,0 +1,104 @@
+/// This module describes what an asset expects as input and what it outputs,
+/// so the client can tell generators and filters apart and build input UI.
+
+use hapi_rs::{
+    node::{HoudiniNode, NodeType as HapiNodeType},
+    parameter::ParmBaseTrait,
+    session::ParmType,
+};
+use serde::Serialize;
+use thiserror::Error;
+
+use super::asset_param::NodeType;
+
+#[derive(Debug, Error, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IoSpecError {
+    #[error("Failed to get input name")]
+    GetInputName,
+    #[error("Failed to get asset parameters")]
+    GetParameters,
+}
+
+type Result<T> = std::result::Result<T, IoSpecError>;
+
+#[derive(Debug, Serialize)]
+pub enum AssetKind {
+    /// The asset has no inputs and creates geometry from scratch.
+    Generator,
+    /// The asset modifies geometry connected to its inputs.
+    Filter,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InputSpec {
+    pub index: i32,
+    pub label: String,
+}
+
+/// A node-path parameter that pulls in geometry with an object merge, rather
+/// than through a node input.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectMergeInputSpec {
+    pub parm_name: String,
+    pub label: Option<String>,
+    pub node_type: NodeType,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetIoSpec {
+    pub kind: AssetKind,
+    pub input_count: i32,
+    pub inputs: Vec<InputSpec>,
+    pub object_merge_inputs: Vec<ObjectMergeInputSpec>,
+    pub output_count: i32,
+}
+
+impl AssetIoSpec {
+    pub(crate) fn from_node(node: &HoudiniNode) -> Result<Self> {
+        let input_count = node.info.input_count();
+        let mut inputs = Vec::with_capacity(input_count.max(0) as usize);
+        for index in 0..input_count {
+            let label = node
+                .get_input_name(index)
+                .map_err(|_| IoSpecError::GetInputName)?;
+            inputs.push(InputSpec { index, label });
+        }
+
+        let mut object_merge_inputs = Vec::new();
+        for param in node.parameters().map_err(|_| IoSpecError::GetParameters)? {
+            let info = param.info();
+            if info.parm_type() != ParmType::Node {
+                continue;
+            }
+
+            let node_type = info.input_node_type();
+            if matches!(node_type, HapiNodeType::None) {
+                continue;
+            }
+
+            object_merge_inputs.push(ObjectMergeInputSpec {
+                parm_name: info.name().unwrap_or_default(),
+                label: info.label().ok(),
+                node_type: node_type.into(),
+            });
+        }
+
+        let kind = if input_count > 0 || !object_merge_inputs.is_empty() {
+            AssetKind::Filter
+        } else {
+            AssetKind::Generator
+        };
+
+        Ok(Self {
+            kind,
+            input_count,
+            inputs,
+            object_merge_inputs,
+            output_count: node.info.output_count(),
+        })
+    }
+}
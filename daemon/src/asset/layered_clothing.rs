@@ -0,0 +1,183 @@
+/// This module splits cooked geometry into the render mesh and cage meshes
+/// used by Roblox layered clothing (`WrapLayer`/`WrapTarget`).
+///
+/// Parts are identified by primitive groups named `render`, `inner_cage` and
+/// `outer_cage`. If a group does not exist, primitives whose `name` attribute
+/// matches the part name are used instead.
+
+use std::collections::HashSet;
+
+use hapi_rs::{geometry::GroupType, node::Geometry, session::AttributeOwner};
+use serde::Serialize;
+use thiserror::Error;
+
+use super::{
+    attribute_data::{read_raw_attribute, AttributeDataError, AttributeValues, TriangleIndices},
+    mesh_data::{GeometrySource, MeshDataError},
+    AttributeFilter, MeshData,
+};
+
+const RENDER_PART: &str = "render";
+const INNER_CAGE_PART: &str = "inner_cage";
+const OUTER_CAGE_PART: &str = "outer_cage";
+
+const NAME_ATTRIBUTE: &str = "name";
+
+/// Maximum difference between matching cage UVs.
+const UV_TOLERANCE: f32 = 1e-4;
+
+#[derive(Debug, Error, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LayeredClothingError {
+    #[error("Failed to get primitive groups")]
+    GetGroups,
+    #[error("Failed to read name attribute")]
+    ReadNameAttribute(AttributeDataError),
+    #[error("No primitives found for {0}")]
+    MissingPart(&'static str),
+    #[error("Cages must have UVs")]
+    MissingUvs,
+    #[error("Inner cage has {inner} vertices but outer cage has {outer}")]
+    VertexCountMismatch { inner: usize, outer: usize },
+    #[error("Inner cage has {inner} triangle corners but outer cage has {outer}")]
+    CornerCountMismatch { inner: usize, outer: usize },
+    #[error("Inner and outer cage UVs don't match at vertex {0}")]
+    UvMismatch(usize),
+    #[error("Failed to build clothing mesh")]
+    BuildMesh(MeshDataError),
+}
+
+type Result<T> = std::result::Result<T, LayeredClothingError>;
+
+/// A render mesh and its cages, ready to be used for a `WrapLayer`. The
+/// cages on their own can be used for a `WrapTarget`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LayeredClothing {
+    pub render_mesh: MeshData,
+    pub inner_cage: MeshData,
+    pub outer_cage: MeshData,
+}
+
+/// Returns which primitives belong to a part, or `None` if no primitive does.
+fn part_mask(
+    geo: &Geometry,
+    source: &GeometrySource,
+    group_names: &HashSet<String>,
+    names: Option<&[String]>,
+    part: &str,
+) -> Result<Option<Vec<bool>>> {
+    let mask = if group_names.contains(part) {
+        geo.get_group_membership(&source.partition, GroupType::Prim, part)
+            .map_err(|_| LayeredClothingError::GetGroups)?
+            .into_iter()
+            .map(|member| member != 0)
+            .collect()
+    } else if let Some(names) = names {
+        names.iter().map(|name| name == part).collect()
+    } else {
+        return Ok(None);
+    };
+
+    Ok(Some(mask).filter(|mask: &Vec<bool>| mask.iter().any(|member| *member)))
+}
+
+/// Checks that both cages have the same topology and UV layout, which Roblox
+/// requires to deform clothing between them.
+fn validate_cages(source: &GeometrySource, inner: &[bool], outer: &[bool]) -> Result<()> {
+    let uvs = source
+        .uvs
+        .as_ref()
+        .ok_or(LayeredClothingError::MissingUvs)?;
+
+    let inner = TriangleIndices::new(&source.face_counts, &source.vertex_list, Some(inner));
+    let outer = TriangleIndices::new(&source.face_counts, &source.vertex_list, Some(outer));
+
+    let inner_points: HashSet<usize> = inner.points.iter().copied().collect();
+    let outer_points: HashSet<usize> = outer.points.iter().copied().collect();
+    if inner_points.len() != outer_points.len() {
+        return Err(LayeredClothingError::VertexCountMismatch {
+            inner: inner_points.len(),
+            outer: outer_points.len(),
+        });
+    }
+    if inner.vertices.len() != outer.vertices.len() {
+        return Err(LayeredClothingError::CornerCountMismatch {
+            inner: inner.vertices.len(),
+            outer: outer.vertices.len(),
+        });
+    }
+
+    for (corner, (a, b)) in inner.vertices.iter().zip(&outer.vertices).enumerate() {
+        let du = (uvs[a * 3] - uvs[b * 3]).abs();
+        let dv = (uvs[a * 3 + 1] - uvs[b * 3 + 1]).abs();
+        if du > UV_TOLERANCE || dv > UV_TOLERANCE {
+            return Err(LayeredClothingError::UvMismatch(corner));
+        }
+    }
+
+    Ok(())
+}
+
+impl LayeredClothing {
+    /// Splits the geometry into its parts. Primitives outside `prim_mask` are
+    /// left out of every part.
+    pub(crate) fn from_source(
+        geo: &Geometry,
+        source: &GeometrySource,
+        prim_mask: Option<&[bool]>,
+        attribute_filter: &AttributeFilter,
+    ) -> Result<Self> {
+        let group_names: HashSet<String> = geo
+            .get_group_names(GroupType::Prim)
+            .map_err(|_| LayeredClothingError::GetGroups)?
+            .into_iter()
+            .collect();
+
+        let names = match read_raw_attribute(
+            geo,
+            source.partition.part_id(),
+            AttributeOwner::Prim,
+            NAME_ATTRIBUTE,
+        )
+        .map_err(LayeredClothingError::ReadNameAttribute)?
+        {
+            Some(raw) => match raw.values {
+                AttributeValues::String(names) => Some(names),
+                _ => None,
+            },
+            None => None,
+        };
+
+        let mask_for = |part: &'static str| -> Result<Vec<bool>> {
+            let mut mask = part_mask(geo, source, &group_names, names.as_deref(), part)?
+                .ok_or(LayeredClothingError::MissingPart(part))?;
+            if let Some(prim_mask) = prim_mask {
+                for (member, included) in mask.iter_mut().zip(prim_mask) {
+                    *member &= *included;
+                }
+            }
+
+            Some(mask)
+                .filter(|mask| mask.iter().any(|member| *member))
+                .ok_or(LayeredClothingError::MissingPart(part))
+        };
+
+        let render = mask_for(RENDER_PART)?;
+        let inner = mask_for(INNER_CAGE_PART)?;
+        let outer = mask_for(OUTER_CAGE_PART)?;
+
+        validate_cages(source, &inner, &outer)?;
+
+        let build = |mask: &[bool]| {
+            MeshData::from_source(geo, source, Some(mask), attribute_filter)
+                .map_err(LayeredClothingError::BuildMesh)
+        };
+
+        Ok(Self {
+            render_mesh: build(&render)?,
+            inner_cage: build(&inner)?,
+            outer_cage: build(&outer)?,
+        })
+    }
+}
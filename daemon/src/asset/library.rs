@@ -0,0 +1,74 @@
+/// This module inspects HDA libraries, which can contain many asset
+/// definitions in one file.
+
+use hapi_rs::asset::AssetLibrary;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::library_index::DefinitionMetadata;
+
+#[derive(Debug, Error, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LibraryError {
+    #[error("Failed to get asset definition names")]
+    GetNames,
+    #[error("Failed to create node for asset definition {0}")]
+    CreateNode(String),
+    #[error("Failed to get asset info for asset definition {0}")]
+    GetAssetInfo(String),
+    #[error("Failed to delete node for asset definition {0}")]
+    DeleteNode(String),
+}
+
+type Result<T> = std::result::Result<T, LibraryError>;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetDefinition {
+    /// Operator name used to instantiate the definition, such as
+    /// `Sop/studio::scatter::1.0`.
+    pub name: String,
+    pub label: String,
+}
+
+/// Returns every asset definition in a library.
+///
+/// Labels are taken from the library's metadata, read from the file without
+/// HAPI. HAPI only reports labels for instantiated assets, so a definition
+/// missing from the metadata gets a node created without cooking it, and
+/// deleted again afterwards.
+pub(crate) fn list_definitions(
+    library: &AssetLibrary,
+    metadata: &[DefinitionMetadata],
+) -> Result<Vec<AssetDefinition>> {
+    let names = library
+        .get_asset_names()
+        .map_err(|_| LibraryError::GetNames)?;
+
+    let mut definitions = Vec::with_capacity(names.len());
+    for name in names {
+        let label = match metadata.iter().find(|definition| definition.name == name) {
+            Some(definition) => definition.label.clone(),
+            None => read_label(library, &name)?,
+        };
+
+        definitions.push(AssetDefinition { name, label });
+    }
+
+    Ok(definitions)
+}
+
+/// Reads the label of a definition by instantiating it. The node is deleted
+/// even if the label can't be read.
+fn read_label(library: &AssetLibrary, name: &str) -> Result<String> {
+    let node = library
+        .create_asset_for_node(name, None)
+        .map_err(|_| LibraryError::CreateNode(name.to_owned()))?;
+
+    let label = node.asset_info().and_then(|info| info.label());
+    let deleted = node.delete();
+
+    let label = label.map_err(|_| LibraryError::GetAssetInfo(name.to_owned()))?;
+    deleted.map_err(|_| LibraryError::DeleteNode(name.to_owned()))?;
+    Ok(label)
+}
@@ -3,10 +3,16 @@
 use std::time::Instant;
 
 use glam::Vec3;
-use hapi_rs::{attribute::NumericAttr, node::Geometry, session::AttributeOwner};
+use hapi_rs::{
+    attribute::NumericAttr, geometry::PartInfo, node::Geometry, session::AttributeOwner,
+};
 use serde::Serialize;
 use thiserror::Error;
 
+use super::attribute_data::{
+    AttributeData, AttributeDataError, AttributeFilter, FilteredAttributes, TriangleIndices,
+};
+
 #[derive(Debug, Error, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum MeshDataError {
@@ -40,6 +46,9 @@ pub enum MeshDataError {
     GetPointColorAttribute,
     #[error("Failed to convert color attribute")]
     ConvertColorAttribute,
+
+    #[error("Failed to get requested attributes")]
+    GetAttributeData(AttributeDataError),
 }
 
 type Result<T> = std::result::Result<T, MeshDataError>;
@@ -51,22 +60,60 @@ pub struct Stats {
     vertex_processing_time: f64,
 }
 
+/// Describes which attributes are interleaved after each position in
+/// `vertexArray`, in order.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VertexLayout {
+    normals: bool,
+    colors: bool,
+    uvs: bool,
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MeshData {
     num_vertices: i32,
+    layout: VertexLayout,
     vertex_array: Vec<Vec3>,
 
+    /// Raw attribute arrays as returned by HAPI. These are only populated for
+    /// the full cooked mesh, not for meshes built from a subset of primitives.
     positions: Vec<f32>,
     normals: Option<Vec<f32>>,
     colors: Option<Vec<f32>>,
     uvs: Option<Vec<f32>>,
 
+    /// Additional attributes requested by the client, aligned with the emitted
+    /// triangles.
+    attributes: Vec<AttributeData>,
+
     stats: Stats,
 }
 
-impl MeshData {
-    pub fn from_houdini_geo(geo: &Geometry) -> Result<Self> {
+/// Raw mesh data read from a geometry partition. Reading from HAPI is the
+/// expensive part of building a mesh, so this is read once per cook and then
+/// shared by every mesh built from the geometry.
+pub(crate) struct GeometrySource {
+    pub partition: PartInfo,
+
+    pub positions: Vec<f32>,
+    pub face_counts: Vec<i32>,
+    pub vertex_list: Vec<i32>,
+    /// Index of each primitive's first vertex in `vertex_list`.
+    pub prim_offsets: Vec<usize>,
+
+    pub uvs: Option<Vec<f32>>,
+    pub normals: Option<Vec<f32>>,
+    pub is_point_normal: bool,
+    pub colors: Option<Vec<f32>>,
+    pub is_point_color: bool,
+
+    pub hapi_time: f64,
+}
+
+impl GeometrySource {
+    pub fn read(geo: &Geometry) -> Result<Self> {
         let start = Instant::now();
 
         // Note: This implementation only supports one partition per geo. This
@@ -161,19 +208,139 @@ impl MeshData {
         };
 
         let hapi_time = Instant::now().duration_since(start);
+
+        let prim_offsets = face_counts
+            .iter()
+            .scan(0, |offset, &count| {
+                let prim_offset = *offset;
+                *offset += count as usize;
+                Some(prim_offset)
+            })
+            .collect();
+
+        Ok(Self {
+            partition,
+
+            positions,
+            face_counts,
+            vertex_list,
+            prim_offsets,
+
+            uvs,
+            normals,
+            is_point_normal,
+            colors,
+            is_point_color,
+
+            hapi_time: hapi_time.as_secs_f64(),
+        })
+    }
+
+    /// Number of primitives in the partition.
+    pub fn prim_count(&self) -> usize {
+        self.face_counts.len()
+    }
+
+    /// Converts point positions from Houdini units to studs, so everything
+    /// built from the geometry is in studs.
+    pub fn convert_to_studs(&mut self, units_per_stud: f32) {
+        for position in &mut self.positions {
+            *position /= units_per_stud;
+        }
+    }
+}
+
+impl MeshData {
+    pub fn from_houdini_geo(geo: &Geometry, attribute_filter: &AttributeFilter) -> Result<Self> {
+        let source = GeometrySource::read(geo)?;
+        Self::from_source(geo, &source, None, attribute_filter)
+    }
+
+    /// Returns the position and color of every emitted triangle corner, in
+    /// `vertexArray` order.
+    pub(crate) fn corners(&self) -> impl Iterator<Item = (Vec3, Option<Vec3>)> + '_ {
+        let color_offset = 1 + self.layout.normals as usize;
+        let stride = color_offset + self.layout.colors as usize + self.layout.uvs as usize;
+        let has_colors = self.layout.colors;
+
+        self.vertex_array
+            .chunks_exact(stride)
+            .map(move |corner| (corner[0], has_colors.then(|| corner[color_offset])))
+    }
+
+    /// Reads the attributes in the allow-list once, so they can be shared by
+    /// every mesh built from the geometry.
+    pub(crate) fn read_attributes(
+        geo: &Geometry,
+        source: &GeometrySource,
+        attribute_filter: &AttributeFilter,
+    ) -> Result<FilteredAttributes> {
+        FilteredAttributes::read(geo, &source.partition, attribute_filter)
+            .map_err(MeshDataError::GetAttributeData)
+    }
+
+    /// Builds a mesh from already-read geometry. If `prim_mask` is given, only
+    /// primitives whose entry is `true` are emitted.
+    pub(crate) fn from_source(
+        geo: &Geometry,
+        source: &GeometrySource,
+        prim_mask: Option<&[bool]>,
+        attribute_filter: &AttributeFilter,
+    ) -> Result<Self> {
+        let start = Instant::now();
+        let attributes = Self::read_attributes(geo, source, attribute_filter)?;
+        let attribute_time = Instant::now().duration_since(start).as_secs_f64();
+
+        let prims: Vec<usize> = (0..source.prim_count())
+            .filter(|&prim| prim_mask.map_or(true, |mask| mask[prim]))
+            .collect();
+        let mut mesh = Self::from_prims(source, &prims, &attributes);
+        mesh.stats.hapi_time += attribute_time;
+
+        if prim_mask.is_none() {
+            mesh.positions = source.positions.clone();
+            mesh.normals = source.normals.clone();
+            mesh.colors = source.colors.clone();
+            mesh.uvs = source.uvs.clone();
+        }
+
+        Ok(mesh)
+    }
+
+    /// Builds a mesh from the given primitives of already-read geometry, in
+    /// order. `attributes` must have been read from the same geometry.
+    pub(crate) fn from_prims(
+        source: &GeometrySource,
+        prims: &[usize],
+        attributes: &FilteredAttributes,
+    ) -> Self {
+        let triangles = TriangleIndices::from_prims(
+            &source.face_counts,
+            &source.vertex_list,
+            &source.prim_offsets,
+            prims,
+        );
+        let attributes = attributes.align(&triangles);
+
+        let hapi_time = source.hapi_time;
         let start = Instant::now();
 
         // Step 2. Process vertices
 
-        let mut num_vertices = (face_counts.iter().sum::<i32>() / 2) * 3;
+        let mut num_vertices = (prims
+            .iter()
+            .map(|&prim| source.face_counts[prim])
+            .sum::<i32>()
+            / 2)
+            * 3;
         num_vertices *= 3; // Position
-        if normals.is_some() {
+        if source.normals.is_some() {
             num_vertices *= 3;
         }
-        if colors.is_some() {
+        if source.colors.is_some() {
             num_vertices *= 3;
         }
-        if uvs.is_some() {
+        if source.uvs.is_some() {
             num_vertices *= 3;
         }
 
@@ -181,10 +348,24 @@ impl MeshData {
         //  bound checked:         200 us
         //  unsafe unchecked Rust: 180 us
 
+        let GeometrySource {
+            positions,
+            vertex_list,
+            normals,
+            is_point_normal,
+            colors,
+            is_point_color,
+            uvs,
+            ..
+        } = source;
+        let (is_point_normal, is_point_color) = (*is_point_normal, *is_point_color);
+
         let mut vertex_array = Vec::with_capacity(num_vertices as usize);
-        let mut offset = 0;
 
-        for vertex_count_per_face in face_counts {
+        for &prim in prims {
+            let vertex_count_per_face = source.face_counts[prim];
+            let offset = source.prim_offsets[prim];
+
             let num_triangles = (vertex_count_per_face - 2) as usize;
             for i in 0..num_triangles {
                 let off0 = offset + 0;
@@ -221,7 +402,7 @@ impl MeshData {
                 vertex_array.push(pos_a);
 
                 // Normals
-                if let Some(ref normals) = normals {
+                if let Some(normals) = normals {
                     let idx = if is_point_normal { point_0_index } else { off0 };
                     vertex_array.push(unsafe {
                         Vec3::new(
@@ -233,7 +414,7 @@ impl MeshData {
                 }
 
                 // Color
-                if let Some(ref colors) = colors {
+                if let Some(colors) = colors {
                     let idx = if is_point_color { point_0_index } else { off0 };
                     vertex_array.push(unsafe {
                         Vec3::new(
@@ -245,7 +426,7 @@ impl MeshData {
                 }
 
                 // UV
-                if let Some(ref uvs) = uvs {
+                if let Some(uvs) = uvs {
                     vertex_array.push(Vec3::new(uvs[off0 * 3 + 0], 1.0 - uvs[off0 * 3 + 1], 0.0));
                 }
 
@@ -253,7 +434,7 @@ impl MeshData {
                 vertex_array.push(pos_b);
 
                 // Normal
-                if let Some(ref normals) = normals {
+                if let Some(normals) = normals {
                     let idx = if is_point_normal { point_1_index } else { off1 };
                     vertex_array.push(unsafe {
                         Vec3::new(
@@ -265,7 +446,7 @@ impl MeshData {
                 }
 
                 // Color
-                if let Some(ref colors) = colors {
+                if let Some(colors) = colors {
                     let idx = if is_point_color { point_1_index } else { off1 };
                     vertex_array.push(unsafe {
                         Vec3::new(
@@ -277,7 +458,7 @@ impl MeshData {
                 }
 
                 // UV
-                if let Some(ref uvs) = uvs {
+                if let Some(uvs) = uvs {
                     vertex_array.push(Vec3::new(uvs[off1 * 3 + 0], 1.0 - uvs[off1 * 3 + 1], 0.0));
                 }
 
@@ -285,7 +466,7 @@ impl MeshData {
                 vertex_array.push(pos_c);
 
                 // Normal
-                if let Some(ref normals) = normals {
+                if let Some(normals) = normals {
                     let idx = if is_point_normal { point_2_index } else { off2 };
                     vertex_array.push(unsafe {
                         Vec3::new(
@@ -297,7 +478,7 @@ impl MeshData {
                 }
 
                 // Color
-                if let Some(ref colors) = colors {
+                if let Some(colors) = colors {
                     let idx = if is_point_color { point_2_index } else { off2 };
                     vertex_array.push(unsafe {
                         Vec3::new(
@@ -309,29 +490,36 @@ impl MeshData {
                 }
 
                 // UV
-                if let Some(ref uvs) = uvs {
+                if let Some(uvs) = uvs {
                     vertex_array.push(Vec3::new(uvs[off2 * 3 + 0], 1.0 - uvs[off2 * 3 + 1], 0.0));
                 }
             }
-
-            offset += vertex_count_per_face as usize;
         }
 
         let vertex_processing_time = Instant::now().duration_since(start);
 
-        Ok(Self {
+        let layout = VertexLayout {
+            normals: source.normals.is_some(),
+            colors: source.colors.is_some(),
+            uvs: source.uvs.is_some(),
+        };
+
+        Self {
             num_vertices,
+            layout,
             vertex_array,
 
-            positions,
-            normals,
-            colors,
-            uvs,
+            positions: Vec::new(),
+            normals: None,
+            colors: None,
+            uvs: None,
+
+            attributes,
 
             stats: Stats {
-                hapi_time: hapi_time.as_secs_f64(),
+                hapi_time,
                 vertex_processing_time: vertex_processing_time.as_secs_f64(),
             },
-        })
+        }
     }
 }
@@ -1,16 +1,65 @@
 mod asset_param;
+mod attribute_data;
+mod collision;
+mod input_geometry;
+mod instance_tree;
+mod io_spec;
+mod layered_clothing;
+mod library;
 mod mesh_data;
+mod multiparm;
+mod param_button;
+mod param_expression;
+mod param_menu;
+mod param_path;
+mod param_state;
+mod param_tree;
+mod param_validate;
+mod param_write;
+mod preset;
+mod preview;
+mod ramp;
+mod skinning;
 
-use std::{path::Path, time::Instant};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Path, PathBuf},
+    time::Instant,
+};
 
 use hapi_rs::{
     node::{AssetInfo, Geometry},
-    session::{CookOptions, HoudiniNode, Session},
+    session::{CookOptions, HoudiniNode, Parameter, ParmType, Session},
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-pub use self::{asset_param::SerializableParameter, mesh_data::MeshData};
+pub use self::{
+    asset_param::{ParamValue, SerializableParameter},
+    attribute_data::{AttributeData, AttributeFilter, AttributeOwnerKind, AttributeValues},
+    collision::{CollisionData, CollisionOptions, ConvexHull},
+    input_geometry::{CurveKind, InputAttribute, InputGeometry, PartBox, METERS_PER_STUD},
+    instance_tree::{InstanceDescription, InstanceTree, PropertyValue},
+    io_spec::{AssetIoSpec, AssetKind, InputSpec, ObjectMergeInputSpec},
+    layered_clothing::LayeredClothing,
+    library::AssetDefinition,
+    mesh_data::MeshData,
+    param_button::ButtonPress,
+    param_menu::{MenuChoice, ParameterMenu},
+    param_path::{PathBase, PathKind},
+    param_state::ParameterState,
+    param_tree::{AssetParameters, ParameterNode},
+    param_validate::{FieldError, FieldErrorKind, WriteOptions},
+    param_write::ParameterWrite,
+    preset::{AppliedPreset, ParameterSnapshot, PresetExpressions, PresetValues},
+    preview::{PreviewOptions, PreviewView},
+    ramp::{RampBasis, RampKey, RampValue},
+    skinning::{BoneDescription, SkinningData},
+};
+
+use crate::library_index::read_library_index;
+
+use self::{collision::collision_proxy_mask, mesh_data::GeometrySource, ramp::RampInfo};
 
 #[derive(Debug, Error, Serialize)]
 #[serde(rename_all = "snake_case")]
@@ -19,26 +68,144 @@ pub enum AssetError {
     LoadAssetFile,
     #[error("Failed to create first asset from asset library")]
     CreateAsset,
+    #[error("Failed to list asset definitions in library")]
+    ListDefinitions(library::LibraryError),
+    #[error("Asset library has no definition named {0}")]
+    DefinitionNotFound(String),
     #[error("Failed to get geometry for asset")]
     GetGeometry,
     #[error("Failed to get asset info")]
     GetAssetInfo,
+    #[error("Failed to get asset input and output specification")]
+    GetIoSpec(io_spec::IoSpecError),
     #[error("Asset has no geometry")]
     NoGeometry,
+    #[error("Failed to delete asset nodes")]
+    DeleteAsset,
 
     #[error("Failed to get asset parameters")]
     GetParameters,
     #[error("Failed to serialize asset parameter")]
     SerializeParameter(asset_param::AssetParamError),
+    #[error("Parameter values are invalid")]
+    InvalidParameters(Vec<FieldError>),
+    #[error("Failed to set asset parameter")]
+    SetParameter(param_write::ParamWriteError),
+    #[error("Failed to set parameter expression")]
+    SetExpression(param_expression::ParamExpressionError),
+    #[error("Failed to resolve file path parameter")]
+    PathParameter(param_path::ParamPathError),
+    #[error("Failed to get parameter visibility")]
+    GetParameterStates(param_state::ParamStateError),
+    #[error("Failed to read ramp parameter")]
+    ReadRamp(ramp::RampError),
+    #[error("Failed to update multiparm instances")]
+    UpdateMultiparm(multiparm::MultiparmError),
+    #[error("Failed to press button parameter")]
+    PressButton(param_button::ParamButtonError),
+    #[error("Failed to read parameter menus")]
+    ReadMenus(param_menu::ParamMenuError),
+
+    #[error("Asset has no input {0}")]
+    InvalidInputIndex(i32),
+    #[error(
+        "Input geometry uses {actual} units per stud, but the asset's other inputs use {expected}"
+    )]
+    UnitsMismatch { expected: f32, actual: f32 },
+    #[error("Units per stud must be a positive number, got {0}")]
+    InvalidUnitsPerStud(f32),
+    #[error("Failed to create input node")]
+    CreateInputNode,
+    #[error("Failed to set input geometry")]
+    SetInputGeometry(input_geometry::InputGeometryError),
+    #[error("Failed to connect input node to asset")]
+    ConnectInput,
+    #[error("Asset has no output {0}")]
+    InvalidOutputIndex(i32),
+    #[error("Failed to disconnect asset input")]
+    DisconnectInput,
 
     #[error("Failed to cook asset")]
     CookAsset,
     #[error("Failed to get mesh data from asset geometry")]
     GetMeshData(mesh_data::MeshDataError),
+    #[error("Failed to build instance tree from asset geometry")]
+    GetInstanceTree(instance_tree::InstanceTreeError),
+    #[error("Failed to get skinning data from asset geometry")]
+    GetSkinning(skinning::SkinningError),
+    #[error("Failed to get layered clothing from asset geometry")]
+    GetLayeredClothing(layered_clothing::LayeredClothingError),
+    #[error("Failed to build collision for asset geometry")]
+    GetCollision(collision::CollisionError),
+    #[error("Failed to render preview of asset geometry")]
+    RenderPreview(preview::PreviewError),
 }
 
 type Result<T> = std::result::Result<T, AssetError>;
 
+impl AssetError {
+    /// Whether the error refers to a parameter, definition or other part of
+    /// the asset that doesn't exist.
+    pub fn is_not_found(&self) -> bool {
+        use multiparm::MultiparmError;
+        use param_button::ParamButtonError;
+        use param_expression::ParamExpressionError;
+        use param_path::ParamPathError;
+        use param_write::ParamWriteError;
+
+        matches!(
+            self,
+            Self::DefinitionNotFound(_)
+                | Self::SetParameter(ParamWriteError::ParameterNotFound(_))
+                | Self::SetExpression(ParamExpressionError::ParameterNotFound(_))
+                | Self::UpdateMultiparm(MultiparmError::ParameterNotFound(_))
+                | Self::PressButton(ParamButtonError::ParameterNotFound(_))
+                | Self::PathParameter(ParamPathError::ParameterNotFound(_))
+        )
+    }
+
+    /// Whether the error was caused by invalid values in the request, rather
+    /// than by Houdini.
+    pub fn is_bad_request(&self) -> bool {
+        use input_geometry::InputGeometryError;
+        use multiparm::MultiparmError;
+        use param_button::ParamButtonError;
+        use param_expression::ParamExpressionError;
+        use param_path::ParamPathError;
+        use param_write::ParamWriteError;
+
+        matches!(
+            self,
+            Self::InvalidParameters(_)
+                | Self::InvalidInputIndex(_)
+                | Self::InvalidOutputIndex(_)
+                | Self::UnitsMismatch { .. }
+                | Self::InvalidUnitsPerStud(_)
+                | Self::SetParameter(
+                    ParamWriteError::TypeMismatch(_) | ParamWriteError::InvalidChoice(_)
+                )
+                | Self::SetExpression(
+                    ParamExpressionError::NotSupported(_)
+                        | ParamExpressionError::InvalidIndex { .. }
+                )
+                | Self::UpdateMultiparm(
+                    MultiparmError::NotMultiparm(_)
+                        | MultiparmError::InvalidIndex { .. }
+                        | MultiparmError::InvalidCount
+                )
+                | Self::PressButton(ParamButtonError::NotButton(_))
+                | Self::PathParameter(ParamPathError::NotPath(_))
+                | Self::SetInputGeometry(
+                    InputGeometryError::InvalidTriangle(_)
+                        | InputGeometryError::NotEnoughCurvePoints(_)
+                        | InputGeometryError::InvalidCurveOrder(_)
+                        | InputGeometryError::BezierPointCount { .. }
+                        | InputGeometryError::AttributeSizeMismatch(_)
+                )
+        )
+    }
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SerializableAssetInfo {
@@ -63,6 +230,29 @@ impl From<AssetInfo> for SerializableAssetInfo {
     }
 }
 
+/// Client-provided options that control how cooked geometry is converted
+/// before it is returned.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ConversionOptions {
+    /// Additional attributes to pass through alongside the mesh.
+    pub attributes: AttributeFilter,
+    /// Whether to interpret `rbx_*` attributes and return an instance tree.
+    pub instance_tree: bool,
+    /// Whether to extract bones and skin weights from capture attributes.
+    pub skinning: bool,
+    /// Whether to split the geometry into a render mesh and cages for
+    /// layered clothing.
+    pub layered_clothing: bool,
+    /// Settings for the collision pass. Collision is only computed if set.
+    pub collision: Option<CollisionOptions>,
+    /// Settings for the preview image. A preview is only rendered if set.
+    pub preview: Option<PreviewOptions>,
+    /// Whether to return the choices of every menu parameter, so menus
+    /// generated from upstream geometry are kept current.
+    pub menus: bool,
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CookStats {
@@ -73,30 +263,76 @@ pub struct CookStats {
 #[serde(rename_all = "camelCase")]
 pub struct CookResult {
     mesh_data: MeshData,
+    instance_tree: Option<InstanceTree>,
+    skinning: Option<SkinningData>,
+    layered_clothing: Option<LayeredClothing>,
+    collision: Option<CollisionData>,
+    menus: Option<Vec<ParameterMenu>>,
+    /// Houdini units per stud that every cooked position was divided by.
+    units_per_stud: f32,
     stats: CookStats,
+    /// PNG preview of the mesh. This is stored by the asset browser rather
+    /// than returned to the client.
+    #[serde(skip)]
+    preview: Option<Vec<u8>>,
+}
+
+impl CookResult {
+    /// Takes the rendered preview image out of the result.
+    pub fn take_preview(&mut self) -> Option<Vec<u8>> {
+        self.preview.take()
+    }
 }
 
 /// Wraps a low-level Houdini Digital Asset (HDA) and provides a higher-level
 /// interface for interacting with it.
+///
+/// Positions are exchanged with the plugin in studs. Each asset has one scale,
+/// `units_per_stud`: input geometry is multiplied by it when uploaded, and
+/// cooked geometry is divided by it, so a pass-through asset returns its
+/// input unchanged. Attribute values passed through as-is aren't converted.
 pub struct Asset {
     internal_asset: HoudiniNode,
     geometry: Geometry,
+    /// Houdini units per stud, set by the latest input geometry.
+    units_per_stud: f32,
+    /// Input nodes created for this asset, keyed by input index. These are
+    /// reused when an input is updated.
+    inputs: HashMap<i32, Geometry>,
 }
 
 impl Asset {
     /// Load an asset from a file. This loads the asset into memory, but does
     /// not cook it or create any meshes.
-    pub fn new_from_path<P: AsRef<Path>>(session: &Session, path: &P) -> Result<Self> {
+    ///
+    /// Libraries can contain several asset definitions. `definition` selects
+    /// one by name, otherwise the first definition in the library is used.
+    pub fn new_from_path<P: AsRef<Path>>(
+        session: &Session,
+        path: &P,
+        definition: Option<&str>,
+    ) -> Result<Self> {
         let asset_library = session
             .load_asset_file(path)
             .map_err(|_| AssetError::LoadAssetFile)?;
 
-        // Note: This implementation only supports one asset per HDA. May need
-        //  revisiting later. Are there any use-cases for multiple assets per
-        //  HDA?
-        let asset = asset_library
-            .try_create_first()
-            .map_err(|_| AssetError::CreateAsset)?;
+        let asset = match definition {
+            Some(definition) => {
+                let names = asset_library
+                    .get_asset_names()
+                    .map_err(|_| AssetError::CreateAsset)?;
+                if !names.iter().any(|name| name == definition) {
+                    return Err(AssetError::DefinitionNotFound(definition.to_owned()));
+                }
+
+                asset_library
+                    .create_asset_for_node(definition, None)
+                    .map_err(|_| AssetError::CreateAsset)?
+            }
+            None => asset_library
+                .try_create_first()
+                .map_err(|_| AssetError::CreateAsset)?,
+        };
 
         // Note: This implementation only supports assets with geometry.
         let geometry = asset
@@ -104,12 +340,71 @@ impl Asset {
             .map_err(|_| AssetError::GetGeometry)?
             .ok_or(AssetError::NoGeometry)?;
 
+        param_path::set_asset_directory_var(&asset).map_err(AssetError::PathParameter)?;
+
         Ok(Self {
             internal_asset: asset,
             geometry,
+            units_per_stud: METERS_PER_STUD,
+            inputs: HashMap::new(),
         })
     }
 
+    /// Deletes the asset's node and the input nodes created for it.
+    pub fn delete(self) -> Result<()> {
+        for input_node in self.inputs.into_values() {
+            input_node
+                .node
+                .delete()
+                .map_err(|_| AssetError::DeleteAsset)?;
+        }
+
+        self.internal_asset
+            .delete()
+            .map_err(|_| AssetError::DeleteAsset)
+    }
+
+    /// Returns the name and label of every asset definition in a library
+    /// file.
+    pub fn list_definitions<P: AsRef<Path>>(
+        session: &Session,
+        path: &P,
+    ) -> Result<Vec<AssetDefinition>> {
+        let asset_library = session
+            .load_asset_file(path)
+            .map_err(|_| AssetError::LoadAssetFile)?;
+
+        let path = path.as_ref();
+        let metadata = read_library_index(path).unwrap_or_else(|err| {
+            log::warn!("Failed to read library metadata for {path:?}: {err}");
+            Vec::new()
+        });
+
+        library::list_definitions(&asset_library, &metadata).map_err(AssetError::ListDefinitions)
+    }
+
+    /// Converts a parameter and its current value to be returned to the client.
+    fn serialize_parameter(&self, param: Parameter) -> Result<SerializableParameter> {
+        let ramp_info = RampInfo::from_parm(param.info());
+        let mut serializable_param =
+            SerializableParameter::try_from(param).map_err(AssetError::SerializeParameter)?;
+
+        // A ramp's value is its key count, and its keys are multiparm
+        // instances. Report the keys as the ramp's value instead.
+        if let Some(ramp_info) = ramp_info {
+            let count = match &serializable_param.current_value {
+                ParamValue::Int { current } => current.first().copied().unwrap_or_default(),
+                _ => 0,
+            };
+            let keys = ramp::read_ramp(&self.internal_asset, &ramp_info, count)
+                .map_err(AssetError::ReadRamp)?;
+
+            serializable_param.current_value = ParamValue::Ramp { keys };
+        }
+
+        Ok(serializable_param)
+    }
+
     /// Returns a list of the asset's parameters and their current values.
     /// The result is serialisable and suitable to return to the client.
     pub fn get_asset_parameters(&self) -> Result<Vec<SerializableParameter>> {
@@ -119,16 +414,291 @@ impl Asset {
             .map_err(|_| AssetError::GetParameters)?;
 
         let mut serializable_params = Vec::new();
+        let mut ramp_ids = HashSet::new();
         for param in internal_params {
-            let serializable_param =
-                SerializableParameter::try_from(param).map_err(AssetError::SerializeParameter)?;
+            let serializable_param = self.serialize_parameter(param)?;
+            if matches!(serializable_param.current_value, ParamValue::Ramp { .. }) {
+                ramp_ids.insert(serializable_param.info.id);
+            }
 
             serializable_params.push(serializable_param);
         }
 
+        serializable_params.retain(|param| !ramp_ids.contains(&param.info.parent_id));
+
         Ok(serializable_params)
     }
 
+    /// Returns the asset's parameters, either as a flat list or arranged into
+    /// the folder layout shown in Houdini.
+    pub fn get_parameters(&self, tree: bool) -> Result<AssetParameters> {
+        let parameters = self.get_asset_parameters()?;
+        Ok(if tree {
+            AssetParameters::Tree(param_tree::build_parameter_tree(parameters))
+        } else {
+            AssetParameters::Flat(parameters)
+        })
+    }
+
+    /// Runs `write`, then returns the visibility and enabled state of the
+    /// `written` parameters and of every parameter whose state changed as a
+    /// result of hide-when and disable-when conditions.
+    fn track_states(
+        &self,
+        written: &[&str],
+        write: impl FnOnce() -> Result<()>,
+    ) -> Result<Vec<ParameterState>> {
+        let before = param_state::read_states(&self.internal_asset)
+            .map_err(AssetError::GetParameterStates)?;
+
+        write()?;
+
+        let after = param_state::read_states(&self.internal_asset)
+            .map_err(AssetError::GetParameterStates)?;
+
+        let mut affected: Vec<ParameterState> = after
+            .into_values()
+            .filter(|state| {
+                let is_written = written
+                    .iter()
+                    .any(|name| state.name.as_deref() == Some(*name));
+                is_written || before.get(&state.id) != Some(state)
+            })
+            .collect();
+        affected.sort_by_key(|state| state.id);
+
+        Ok(affected)
+    }
+
+    /// Writes new parameter values, then returns the state of every affected
+    /// parameter.
+    ///
+    /// Nothing is written if any value is invalid, or if a write would replace
+    /// an expression without `options.overwrite_expressions`.
+    pub fn set_parameters(
+        &self,
+        writes: &[ParameterWrite],
+        options: &WriteOptions,
+    ) -> Result<Vec<ParameterState>> {
+        let writes = param_validate::validate_writes(&self.internal_asset, writes, options)
+            .map_err(AssetError::InvalidParameters)?;
+        let written: Vec<&str> = writes.iter().map(|write| write.name.as_str()).collect();
+
+        self.track_states(&written, || {
+            for write in &writes {
+                param_write::write_parameter(&self.internal_asset, write)
+                    .map_err(AssetError::SetParameter)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Sets the expression of one component of a parameter, or removes it if
+    /// `expression` is `None`, then returns the state of every affected
+    /// parameter.
+    pub fn set_parameter_expression(
+        &self,
+        name: &str,
+        index: i32,
+        expression: Option<&str>,
+    ) -> Result<Vec<ParameterState>> {
+        self.track_states(&[name], || {
+            param_expression::set_expression(&self.internal_asset, name, index, expression)
+                .map_err(AssetError::SetExpression)
+        })
+    }
+
+    /// Returns the current values of the asset's parameters, to be saved as
+    /// a preset.
+    pub fn get_preset_values(&self) -> Result<PresetValues> {
+        Ok(preset::capture(self.get_asset_parameters()?))
+    }
+
+    /// Returns the values and expressions of the named parameters, or of every
+    /// parameter if `names` isn't set. The instances of named multiparms are
+    /// included, since adding or removing instances renames them.
+    pub fn snapshot_parameters(&self, names: Option<&[&str]>) -> Result<ParameterSnapshot> {
+        let Some(names) = names else {
+            return Ok(preset::snapshot(self.get_asset_parameters()?));
+        };
+
+        let internal_params = self
+            .internal_asset
+            .parameters()
+            .map_err(|_| AssetError::GetParameters)?;
+
+        // Parameters are listed after their parents, so one pass finds every
+        // parameter inside a named multiparm. Ramp keys are part of the ramp's
+        // value, so they are left out.
+        let mut multiparm_ids = HashSet::new();
+        let mut parameters = Vec::new();
+        for param in internal_params {
+            let info = param.info();
+            let is_named = info
+                .name()
+                .map_or(false, |name| names.contains(&name.as_str()));
+            if !is_named && !multiparm_ids.contains(&info.parent_id().0) {
+                continue;
+            }
+
+            if info.parm_type() == ParmType::Multiparmlist && RampInfo::from_parm(info).is_none() {
+                multiparm_ids.insert(info.id().0);
+            }
+            parameters.push(self.serialize_parameter(param)?);
+        }
+
+        Ok(preset::snapshot(parameters))
+    }
+
+    /// Restores the values and expressions of a snapshot, skipping parameters
+    /// the asset doesn't have.
+    pub fn restore_snapshot(&self, snapshot: &ParameterSnapshot) -> Result<AppliedPreset> {
+        let mut applied = self.apply_preset(&snapshot.values)?;
+
+        let names: Vec<&str> = snapshot.expressions.keys().map(String::as_str).collect();
+        // Writing the values replaced the expressions, so they are set again.
+        let states = self.track_states(&names, || {
+            for (name, expressions) in &snapshot.expressions {
+                if applied.skipped.contains(name) {
+                    continue;
+                }
+
+                for (index, expression) in expressions.iter().enumerate() {
+                    if let Some(expression) = expression {
+                        param_expression::set_expression(
+                            &self.internal_asset,
+                            name,
+                            index as i32,
+                            Some(expression),
+                        )
+                        .map_err(AssetError::SetExpression)?;
+                    }
+                }
+            }
+            Ok(())
+        })?;
+
+        let mut all_states: BTreeMap<_, _> = applied
+            .states
+            .into_iter()
+            .map(|state| (state.id, state))
+            .collect();
+        for state in states {
+            all_states.insert(state.id, state);
+        }
+        applied.states = all_states.into_values().collect();
+
+        Ok(applied)
+    }
+
+    /// Writes the values of a preset, skipping parameters the asset doesn't
+    /// have or whose values don't fit them anymore. Values are clamped to each
+    /// parameter's range, and replace any expressions.
+    pub fn apply_preset(&self, values: &PresetValues) -> Result<AppliedPreset> {
+        let options = WriteOptions {
+            clamp: true,
+            overwrite_expressions: true,
+        };
+
+        let (multiparms, others): (Vec<_>, Vec<_>) = values
+            .iter()
+            .partition(|(name, _)| preset::is_multiparm(&self.internal_asset, name));
+
+        let mut states = BTreeMap::new();
+        let mut skipped = Vec::new();
+        // Instances are created when multiparm counts are written, so which
+        // other parameters exist is only known afterwards.
+        for group in [multiparms, others] {
+            let writes: Vec<_> = group
+                .into_iter()
+                .map(|(name, value)| ParameterWrite {
+                    name: name.clone(),
+                    value: value.clone(),
+                })
+                .collect();
+
+            // Rejected writes are skipped up front, so a mismatch can't fail
+            // the preset after some of it was written.
+            let rejected: HashSet<String> =
+                param_validate::validate_writes(&self.internal_asset, &writes, &options)
+                    .err()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|error| error.name)
+                    .collect();
+            let (writes, rejected): (Vec<_>, Vec<_>) = writes
+                .into_iter()
+                .partition(|write| !rejected.contains(&write.name));
+            skipped.extend(rejected.into_iter().map(|write| write.name));
+
+            for state in self.set_parameters(&writes, &options)? {
+                states.insert(state.id, state);
+            }
+        }
+
+        Ok(AppliedPreset {
+            states: states.into_values().collect(),
+            skipped,
+        })
+    }
+
+    /// Returns the kind of path held by a file path parameter.
+    pub fn path_parameter_kind(&self, name: &str) -> Result<PathKind> {
+        param_path::path_kind(&self.internal_asset, name).map_err(AssetError::PathParameter)
+    }
+
+    /// Returns the directory that paths are stored relative to, if any.
+    pub fn path_base_directory(&self, base: PathBase) -> Result<Option<PathBuf>> {
+        param_path::base_directory(&self.internal_asset, base).map_err(AssetError::PathParameter)
+    }
+
+    /// Writes a chosen path into a file path parameter, relative to `base` if
+    /// the path is inside it. Returns the written value and the state of every
+    /// affected parameter.
+    pub fn set_path_parameter(
+        &self,
+        name: &str,
+        path: &Path,
+        base: PathBase,
+        options: &WriteOptions,
+    ) -> Result<(String, Vec<ParameterState>)> {
+        let value = param_path::parameter_value(&self.internal_asset, path, base)
+            .map_err(AssetError::PathParameter)?;
+        let write = ParameterWrite {
+            name: name.to_owned(),
+            value: ParamValue::String {
+                current: vec![value.clone()],
+            },
+        };
+        let states = self.set_parameters(&[write], options)?;
+
+        Ok((value, states))
+    }
+
+    /// Inserts a multiparm instance before the instance at `index`, or
+    /// appends one if `index` is the instance count.
+    pub fn insert_multiparm_instance(&self, name: &str, index: i32) -> Result<()> {
+        multiparm::insert_instance(&self.internal_asset, name, index)
+            .map_err(AssetError::UpdateMultiparm)
+    }
+
+    /// Removes the multiparm instance at `index`.
+    pub fn remove_multiparm_instance(&self, name: &str, index: i32) -> Result<()> {
+        multiparm::remove_instance(&self.internal_asset, name, index)
+            .map_err(AssetError::UpdateMultiparm)
+    }
+
+    /// Adds or removes multiparm instances at the end until there are `count`.
+    pub fn set_multiparm_count(&self, name: &str, count: i32) -> Result<()> {
+        multiparm::set_instance_count(&self.internal_asset, name, count)
+            .map_err(AssetError::UpdateMultiparm)
+    }
+
+    /// Presses a button parameter and returns what its callback reported.
+    pub fn press_button(&self, name: &str) -> Result<ButtonPress> {
+        param_button::press_button(&self.internal_asset, name).map_err(AssetError::PressButton)
+    }
+
     /// Returns simple information about the HDA.
     pub fn get_asset_info(&self) -> Result<SerializableAssetInfo> {
         let asset_info = self
@@ -139,10 +709,101 @@ impl Asset {
         Ok(asset_info.into())
     }
 
-    /// Cook the asset and return the resulting mesh data. This can be used to
-    /// either create an `EditableMesh` on Roblox, or bake to a final mesh
-    /// in the Daemon.
-    pub fn cook_asset(&self) -> Result<CookResult> {
+    /// Returns the operator name of the asset's definition, such as
+    /// `Sop/studio::scatter::1.0`.
+    pub fn definition_name(&self) -> Result<String> {
+        self.internal_asset
+            .asset_info()
+            .and_then(|info| info.full_op_name())
+            .map_err(|_| AssetError::GetAssetInfo)
+    }
+
+    /// Returns the inputs the asset expects and how many outputs it has.
+    pub fn get_io_spec(&self) -> Result<AssetIoSpec> {
+        AssetIoSpec::from_node(&self.internal_asset).map_err(AssetError::GetIoSpec)
+    }
+
+    /// Sets the geometry connected to one of the asset's inputs. The input
+    /// node is created the first time an input is set, and reused after that.
+    /// `units_per_stud` converts positions from studs to Houdini units, and
+    /// becomes the asset's scale. It can only change while no other input
+    /// holds geometry uploaded with the old scale.
+    pub fn set_input_geometry(
+        &mut self,
+        input_index: i32,
+        geometry: &InputGeometry,
+        units_per_stud: f32,
+    ) -> Result<()> {
+        if input_index < 0 || input_index >= self.internal_asset.info.input_count() {
+            return Err(AssetError::InvalidInputIndex(input_index));
+        }
+        if !(units_per_stud.is_finite() && units_per_stud > 0.0) {
+            return Err(AssetError::InvalidUnitsPerStud(units_per_stud));
+        }
+        let has_other_inputs = self.inputs.keys().any(|&index| index != input_index);
+        if units_per_stud != self.units_per_stud && has_other_inputs {
+            return Err(AssetError::UnitsMismatch {
+                expected: self.units_per_stud,
+                actual: units_per_stud,
+            });
+        }
+
+        if !self.inputs.contains_key(&input_index) {
+            let input_node = self
+                .internal_asset
+                .session
+                .create_input_node(&format!("input_{input_index}"))
+                .map_err(|_| AssetError::CreateInputNode)?;
+            self.inputs.insert(input_index, input_node);
+        }
+        let input_node = &self.inputs[&input_index];
+
+        geometry
+            .upload(input_node, units_per_stud)
+            .map_err(AssetError::SetInputGeometry)?;
+
+        self.internal_asset
+            .connect_input(input_index, &input_node.node, 0)
+            .map_err(|_| AssetError::ConnectInput)?;
+        self.units_per_stud = units_per_stud;
+
+        Ok(())
+    }
+
+    /// Connects an output of another asset to one of this asset's inputs,
+    /// replacing any input geometry previously set on it.
+    pub fn connect_asset_input(
+        &self,
+        input_index: i32,
+        source: &Asset,
+        output_index: i32,
+    ) -> Result<()> {
+        if input_index < 0 || input_index >= self.internal_asset.info.input_count() {
+            return Err(AssetError::InvalidInputIndex(input_index));
+        }
+        if output_index < 0 || output_index >= source.internal_asset.info.output_count() {
+            return Err(AssetError::InvalidOutputIndex(output_index));
+        }
+
+        self.internal_asset
+            .connect_input(input_index, &source.internal_asset, output_index)
+            .map_err(|_| AssetError::ConnectInput)
+    }
+
+    /// Disconnects whatever is connected to one of the asset's inputs.
+    pub fn disconnect_input(&self, input_index: i32) -> Result<()> {
+        if input_index < 0 || input_index >= self.internal_asset.info.input_count() {
+            return Err(AssetError::InvalidInputIndex(input_index));
+        }
+
+        self.internal_asset
+            .disconnect_input(input_index)
+            .map_err(|_| AssetError::DisconnectInput)
+    }
+
+    /// Cooks the asset's geometry without converting it, returning how long
+    /// the cook took in seconds.
+    pub fn cook(&self) -> Result<f64> {
         let start_time = Instant::now();
 
         let cook_options = CookOptions::default();
@@ -150,16 +811,106 @@ impl Asset {
             .node
             .cook_with_options(&cook_options, true)
             .map_err(|_| AssetError::CookAsset)?;
-        let cook_time = Instant::now().duration_since(start_time);
 
-        let mesh_data =
-            MeshData::from_houdini_geo(&self.geometry).map_err(AssetError::GetMeshData)?;
+        Ok(Instant::now().duration_since(start_time).as_secs_f64())
+    }
+
+    /// Cook the asset and return the resulting mesh data. This can be used to
+    /// either create an `EditableMesh` on Roblox, or bake to a final mesh
+    /// in the Daemon.
+    pub fn cook_asset(&self, options: &ConversionOptions) -> Result<CookResult> {
+        let cook_time = self.cook()?;
+
+        let mut source = GeometrySource::read(&self.geometry).map_err(AssetError::GetMeshData)?;
+        source.convert_to_studs(self.units_per_stud);
+
+        // A collision proxy provided by the HDA is not part of the render mesh.
+        let mut render_mask = None;
+        let collision = match &options.collision {
+            Some(collision_options) => {
+                let proxy_mask = collision_proxy_mask(&self.geometry, &source)
+                    .map_err(AssetError::GetCollision)?;
+                let collision =
+                    CollisionData::from_source(&source, proxy_mask.as_deref(), collision_options)
+                        .map_err(AssetError::GetCollision)?;
+
+                render_mask =
+                    proxy_mask.map(|mask| mask.into_iter().map(|proxy| !proxy).collect::<Vec<_>>());
+                Some(collision)
+            }
+            None => None,
+        };
+
+        let mesh_data = MeshData::from_source(
+            &self.geometry,
+            &source,
+            render_mask.as_deref(),
+            &options.attributes,
+        )
+        .map_err(AssetError::GetMeshData)?;
+
+        let instance_tree = if options.instance_tree {
+            Some(
+                InstanceTree::from_source(
+                    &self.geometry,
+                    &source,
+                    render_mask.as_deref(),
+                    &options.attributes,
+                )
+                .map_err(AssetError::GetInstanceTree)?,
+            )
+        } else {
+            None
+        };
+
+        let skinning = if options.skinning {
+            let mut skinning =
+                SkinningData::from_source(&self.geometry, &source, render_mask.as_deref())
+                    .map_err(AssetError::GetSkinning)?;
+            skinning.convert_to_studs(self.units_per_stud);
+            Some(skinning)
+        } else {
+            None
+        };
+
+        let layered_clothing = if options.layered_clothing {
+            Some(
+                LayeredClothing::from_source(
+                    &self.geometry,
+                    &source,
+                    render_mask.as_deref(),
+                    &options.attributes,
+                )
+                .map_err(AssetError::GetLayeredClothing)?,
+            )
+        } else {
+            None
+        };
+
+        let preview = match &options.preview {
+            Some(preview_options) => Some(
+                preview::render_preview(&mesh_data, preview_options)
+                    .map_err(AssetError::RenderPreview)?,
+            ),
+            None => None,
+        };
+
+        let menus = if options.menus {
+            Some(param_menu::read_menus(&self.internal_asset).map_err(AssetError::ReadMenus)?)
+        } else {
+            None
+        };
 
         Ok(CookResult {
             mesh_data,
-            stats: CookStats {
-                cook_time: cook_time.as_secs_f64(),
-            },
+            instance_tree,
+            skinning,
+            layered_clothing,
+            collision,
+            menus,
+            units_per_stud: self.units_per_stud,
+            stats: CookStats { cook_time },
+            preview,
         })
     }
 }
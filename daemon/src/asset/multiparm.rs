@@ -0,0 +1,110 @@
+/// This module adds and removes instances of multiparm blocks.
+///
+/// A multiparm's value is its instance count. Instances are numbered from the
+/// multiparm's instance start offset, which is usually 1, and Houdini renames
+/// the parameters of later instances when one is inserted or removed. Clients
+/// address instances by a zero-based index instead.
+
+use hapi_rs::{
+    node::HoudiniNode,
+    parameter::ParmBaseTrait,
+    session::{Parameter, ParmType},
+};
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Debug, Error, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MultiparmError {
+    #[error("Asset has no parameter named {0}")]
+    ParameterNotFound(String),
+    #[error("Parameter {0} is not a multiparm")]
+    NotMultiparm(String),
+    #[error("Multiparm {name} has no instance {index}")]
+    InvalidIndex { name: String, index: i32 },
+    #[error("Instance count must not be negative")]
+    InvalidCount,
+    #[error("Failed to change instances of multiparm {0}")]
+    UpdateInstances(String),
+}
+
+type Result<T> = std::result::Result<T, MultiparmError>;
+
+struct Multiparm {
+    param: Parameter,
+    name: String,
+}
+
+impl Multiparm {
+    fn find(node: &HoudiniNode, name: &str) -> Result<Self> {
+        let param = node
+            .parameter(name)
+            .map_err(|_| MultiparmError::ParameterNotFound(name.to_owned()))?;
+        if param.info().parm_type() != ParmType::Multiparmlist {
+            return Err(MultiparmError::NotMultiparm(name.to_owned()));
+        }
+
+        Ok(Self {
+            param,
+            name: name.to_owned(),
+        })
+    }
+
+    fn update_failed(&self) -> MultiparmError {
+        MultiparmError::UpdateInstances(self.name.clone())
+    }
+
+    fn count(&self) -> Result<i32> {
+        match &self.param {
+            Parameter::Int(param) => param.get(0).map_err(|_| self.update_failed()),
+            _ => Err(MultiparmError::NotMultiparm(self.name.clone())),
+        }
+    }
+
+    /// Converts a zero-based index to the instance number used by HAPI.
+    /// `max` is the largest valid index.
+    fn position(&self, index: i32, max: i32) -> Result<i32> {
+        if index < 0 || index > max {
+            return Err(MultiparmError::InvalidIndex {
+                name: self.name.clone(),
+                index,
+            });
+        }
+        Ok(self.param.info().instance_start_offset() + index)
+    }
+}
+
+/// Inserts a new instance before the instance at `index`. An index equal to
+/// the instance count appends the instance.
+pub(crate) fn insert_instance(node: &HoudiniNode, name: &str, index: i32) -> Result<()> {
+    let multiparm = Multiparm::find(node, name)?;
+    let position = multiparm.position(index, multiparm.count()?)?;
+    multiparm
+        .param
+        .insert_multiparm_instance(position)
+        .map_err(|_| multiparm.update_failed())
+}
+
+/// Removes the instance at `index`, renumbering the instances after it.
+pub(crate) fn remove_instance(node: &HoudiniNode, name: &str, index: i32) -> Result<()> {
+    let multiparm = Multiparm::find(node, name)?;
+    let position = multiparm.position(index, multiparm.count()? - 1)?;
+    multiparm
+        .param
+        .remove_multiparm_instance(position)
+        .map_err(|_| multiparm.update_failed())
+}
+
+/// Adds or removes instances at the end of the multiparm until it has
+/// `count` instances.
+pub(crate) fn set_instance_count(node: &HoudiniNode, name: &str, count: i32) -> Result<()> {
+    if count < 0 {
+        return Err(MultiparmError::InvalidCount);
+    }
+
+    let multiparm = Multiparm::find(node, name)?;
+    match &multiparm.param {
+        Parameter::Int(param) => param.set(0, count).map_err(|_| multiparm.update_failed()),
+        _ => Err(MultiparmError::NotMultiparm(name.to_owned())),
+    }
+}
@@ -0,0 +1,68 @@
+/// This module presses button parameters. Houdini runs a button's callback
+/// script when its value is set, so pressing a button can change other
+/// parameters, regenerate seeds or write files.
+
+use hapi_rs::{
+    node::HoudiniNode,
+    session::{Parameter, StatusType, StatusVerbosity},
+};
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Debug, Error, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParamButtonError {
+    #[error("Asset has no parameter named {0}")]
+    ParameterNotFound(String),
+    #[error("Parameter {0} is not a button")]
+    NotButton(String),
+}
+
+type Result<T> = std::result::Result<T, ParamButtonError>;
+
+/// What Houdini reported while running a button's callback.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ButtonPress {
+    pub messages: Vec<String>,
+    /// Errors raised by the callback. The press itself still happened.
+    pub errors: Vec<String>,
+}
+
+fn status_lines(status: &str) -> impl Iterator<Item = String> + '_ {
+    status
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_owned)
+}
+
+/// Presses a button parameter, running its callback in the session. A failing
+/// callback is reported in the result rather than as an error, since the
+/// client usually wants to show it next to the button.
+pub(crate) fn press_button(node: &HoudiniNode, name: &str) -> Result<ButtonPress> {
+    let param = node
+        .parameter(name)
+        .map_err(|_| ParamButtonError::ParameterNotFound(name.to_owned()))?;
+    let Parameter::Button(button) = &param else {
+        return Err(ParamButtonError::NotButton(name.to_owned()));
+    };
+
+    let mut press = ButtonPress::default();
+    let result = button.set(0, 1);
+
+    // The call result status describes the last API call, so it has to be
+    // read before anything else talks to the session. The cook result would
+    // describe the previous cook, not the callback.
+    let status = node
+        .session
+        .get_status_string(StatusType::CallResult, StatusVerbosity::Statusverbosity2);
+
+    if let Err(error) = result {
+        press.errors.push(error.to_string());
+    } else if let Ok(status) = status {
+        press.messages.extend(status_lines(&status));
+    }
+
+    Ok(press)
+}
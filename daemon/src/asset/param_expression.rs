@@ -0,0 +1,79 @@
+/// This module reads and edits parameter expressions, such as `$F`, `ch()`
+/// references and Python expressions.
+///
+/// Each component of a float, int or string tuple can have its own
+/// expression. A component with an expression reports the evaluated value as
+/// its current value, and writing a plain value to it replaces the expression.
+
+use hapi_rs::{node::HoudiniNode, parameter::ParmBaseTrait, session::Parameter};
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Debug, Error, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParamExpressionError {
+    #[error("Asset has no parameter named {0}")]
+    ParameterNotFound(String),
+    #[error("Parameter {0} can't have expressions")]
+    NotSupported(String),
+    #[error("Parameter {name} has no component {index}")]
+    InvalidIndex { name: String, index: i32 },
+    #[error("Failed to read expressions of parameter {0}")]
+    ReadExpression(String),
+    #[error("Failed to set expression of parameter {0}")]
+    SetExpression(String),
+}
+
+type Result<T> = std::result::Result<T, ParamExpressionError>;
+
+fn supports_expressions(param: &Parameter) -> bool {
+    matches!(
+        param,
+        Parameter::Float(_) | Parameter::Int(_) | Parameter::String(_)
+    )
+}
+
+/// Returns the expression of each component of a parameter, or an empty list
+/// if the parameter can't have expressions.
+pub(crate) fn read_expressions(param: &Parameter) -> Result<Vec<Option<String>>> {
+    if !supports_expressions(param) {
+        return Ok(Vec::new());
+    }
+
+    (0..param.info().size())
+        .map(|index| {
+            param.expression(index).map_err(|_| {
+                ParamExpressionError::ReadExpression(param.info().name().unwrap_or_default())
+            })
+        })
+        .collect()
+}
+
+/// Sets the expression of one component of a parameter, or removes it if
+/// `expression` is `None`. Removing an expression keeps its last evaluated
+/// value.
+pub(crate) fn set_expression(
+    node: &HoudiniNode,
+    name: &str,
+    index: i32,
+    expression: Option<&str>,
+) -> Result<()> {
+    let param = node
+        .parameter(name)
+        .map_err(|_| ParamExpressionError::ParameterNotFound(name.to_owned()))?;
+    if !supports_expressions(&param) {
+        return Err(ParamExpressionError::NotSupported(name.to_owned()));
+    }
+    if index < 0 || index >= param.info().size() {
+        return Err(ParamExpressionError::InvalidIndex {
+            name: name.to_owned(),
+            index,
+        });
+    }
+
+    match expression {
+        Some(expression) => param.set_expression(expression, index),
+        None => param.remove_expression(index),
+    }
+    .map_err(|_| ParamExpressionError::SetExpression(name.to_owned()))
+}
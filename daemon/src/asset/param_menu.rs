@@ -0,0 +1,107 @@
+/// This module reads the choice lists of menu parameters.
+///
+/// Both int and string parameters can have menus. An int menu's value is the
+/// index of the selected choice, while a string menu's value is the token of
+/// the selected choice. Replace and toggle menus on string parameters only
+/// suggest values, so their value may not be one of the choices.
+///
+/// Menus generated by scripts are evaluated by Houdini when they are read, so
+/// reading them again after a cook picks up changes from upstream geometry.
+
+use hapi_rs::{
+    geometry::ChoiceListType, node::HoudiniNode, parameter::ParmBaseTrait, session::Parameter,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Error, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParamMenuError {
+    #[error("Failed to get asset parameters")]
+    GetParameters,
+    #[error("Failed to read menu choices of parameter {0}")]
+    ReadChoices(String),
+}
+
+type Result<T> = std::result::Result<T, ParamMenuError>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MenuChoice {
+    /// Token stored in the parameter when this choice is selected.
+    pub value: String,
+    pub label: String,
+}
+
+/// The current choices of a menu parameter.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParameterMenu {
+    pub name: String,
+    pub choices: Vec<MenuChoice>,
+}
+
+fn param_name(param: &Parameter) -> String {
+    param.info().name().unwrap_or_default()
+}
+
+/// Returns the choices of an int or string parameter, or `None` if it doesn't
+/// have a menu.
+pub(crate) fn read_choices(param: &Parameter) -> Result<Option<Vec<MenuChoice>>> {
+    if param.info().choice_list_type() == ChoiceListType::None {
+        return Ok(None);
+    }
+
+    let items = match param {
+        Parameter::Int(param) => param.menu_items(),
+        Parameter::String(param) => param.menu_items(),
+        _ => return Ok(None),
+    }
+    .map_err(|_| ParamMenuError::ReadChoices(param_name(param)))?;
+
+    let Some(items) = items else {
+        return Ok(None);
+    };
+
+    items
+        .into_iter()
+        .map(|item| {
+            Ok(MenuChoice {
+                value: item
+                    .value()
+                    .map_err(|_| ParamMenuError::ReadChoices(param_name(param)))?,
+                label: item
+                    .label()
+                    .map_err(|_| ParamMenuError::ReadChoices(param_name(param)))?,
+            })
+        })
+        .collect::<Result<_>>()
+        .map(Some)
+}
+
+/// Returns whether a string menu only accepts values from its choice list.
+pub(crate) fn is_strict(param: &Parameter) -> bool {
+    matches!(
+        param.info().choice_list_type(),
+        ChoiceListType::Normal | ChoiceListType::Mini
+    )
+}
+
+/// Reads the choices of every menu parameter on a node.
+pub(crate) fn read_menus(node: &HoudiniNode) -> Result<Vec<ParameterMenu>> {
+    let params = node
+        .parameters()
+        .map_err(|_| ParamMenuError::GetParameters)?;
+
+    let mut menus = Vec::new();
+    for param in params {
+        if let Some(choices) = read_choices(&param)? {
+            menus.push(ParameterMenu {
+                name: param_name(&param),
+                choices,
+            });
+        }
+    }
+
+    Ok(menus)
+}
@@ -0,0 +1,172 @@
+/// This module supports file path parameters, which the plugin can't fill in
+/// itself since it has no access to the user's disk.
+///
+/// The daemon opens a native picker suited to the parameter's type and writes
+/// the chosen path back. Paths can be stored relative to the HDA's directory
+/// or to `$HIP`, through a variable so Houdini resolves them itself. A path
+/// outside the chosen base directory is stored as an absolute path.
+
+use std::path::{Path, PathBuf};
+
+use hapi_rs::{node::HoudiniNode, parameter::ParmBaseTrait, session::ParmType};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Error, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParamPathError {
+    #[error("Asset has no parameter named {0}")]
+    ParameterNotFound(String),
+    #[error("Parameter {0} is not a file path")]
+    NotPath(String),
+    #[error("Failed to find the base directory for the path")]
+    GetBaseDirectory,
+    #[error("Failed to set the variable for the asset's directory")]
+    SetAssetDirectoryVar,
+}
+
+type Result<T> = std::result::Result<T, ParamPathError>;
+
+const GEOMETRY_EXTENSIONS: &[&str] = &[
+    "bgeo", "sc", "geo", "obj", "fbx", "abc", "usd", "usda", "usdc", "ply", "stl",
+];
+const IMAGE_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "exr", "hdr", "tif", "tiff", "tga", "rat", "pic",
+];
+
+/// What kind of path a parameter holds, which decides the picker's filters.
+#[derive(Debug, Clone, Copy)]
+pub enum PathKind {
+    File,
+    Geometry,
+    Image,
+    Directory,
+}
+
+impl PathKind {
+    fn from_parm_type(parm_type: ParmType) -> Option<Self> {
+        match parm_type {
+            ParmType::PathFile => Some(Self::File),
+            ParmType::PathFileGeo => Some(Self::Geometry),
+            ParmType::PathFileImage => Some(Self::Image),
+            ParmType::PathFileDir => Some(Self::Directory),
+            _ => None,
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::File => "Select File",
+            Self::Geometry => "Select Geometry",
+            Self::Image => "Select Image",
+            Self::Directory => "Select Folder",
+        }
+    }
+
+    pub fn filters(self) -> Vec<(&'static str, &'static [&'static str])> {
+        match self {
+            Self::Geometry => vec![("Geometry", GEOMETRY_EXTENSIONS)],
+            Self::Image => vec![("Images", IMAGE_EXTENSIONS)],
+            Self::File | Self::Directory => Vec::new(),
+        }
+    }
+
+    pub fn picks_folder(self) -> bool {
+        matches!(self, Self::Directory)
+    }
+}
+
+/// Where a chosen path is stored relative to.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PathBase {
+    #[default]
+    Absolute,
+    /// Relative to the directory of the asset's library file, written as
+    /// `$AM_ASSET_DIR_<hash>/...`.
+    AssetDirectory,
+    /// Relative to `$HIP`, written as `$HIP/...`.
+    Hip,
+}
+
+/// Returns the kind of path held by a parameter.
+pub(crate) fn path_kind(node: &HoudiniNode, name: &str) -> Result<PathKind> {
+    let param = node
+        .parameter(name)
+        .map_err(|_| ParamPathError::ParameterNotFound(name.to_owned()))?;
+    PathKind::from_parm_type(param.info().parm_type())
+        .ok_or_else(|| ParamPathError::NotPath(name.to_owned()))
+}
+
+fn to_houdini_path(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+fn asset_directory(node: &HoudiniNode) -> Result<PathBuf> {
+    let library = node
+        .asset_info()
+        .and_then(|info| info.file_path())
+        .map_err(|_| ParamPathError::GetBaseDirectory)?;
+    Ok(PathBuf::from(library)
+        .parent()
+        .ok_or(ParamPathError::GetBaseDirectory)?
+        .to_owned())
+}
+
+/// Returns the name of the variable holding an asset directory. Assets from
+/// different directories share a session, so the name is derived from the
+/// directory with a hash that stays the same between runs.
+fn asset_directory_var(directory: &Path) -> String {
+    // 64-bit FNV-1a.
+    let hash = to_houdini_path(directory)
+        .bytes()
+        .fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+    format!("AM_ASSET_DIR_{hash:016X}")
+}
+
+/// Sets the variable that paths relative to an asset's directory are written
+/// with. Must be called when the asset is loaded, so stored paths resolve
+/// before they're written again.
+pub(crate) fn set_asset_directory_var(node: &HoudiniNode) -> Result<()> {
+    let directory = asset_directory(node)?;
+    node.session
+        .set_server_var::<str>(
+            &asset_directory_var(&directory),
+            &to_houdini_path(&directory),
+        )
+        .map_err(|_| ParamPathError::SetAssetDirectoryVar)
+}
+
+/// Returns the directory that a path is stored relative to, if any.
+pub(crate) fn base_directory(node: &HoudiniNode, base: PathBase) -> Result<Option<PathBuf>> {
+    let directory = match base {
+        PathBase::Absolute => return Ok(None),
+        PathBase::AssetDirectory => asset_directory(node)?,
+        PathBase::Hip => node
+            .session
+            .get_server_var::<str>("HIP")
+            .map(PathBuf::from)
+            .map_err(|_| ParamPathError::GetBaseDirectory)?,
+    };
+
+    Ok(Some(directory))
+}
+
+/// Converts a chosen path into the value stored in the parameter.
+pub(crate) fn parameter_value(node: &HoudiniNode, path: &Path, base: PathBase) -> Result<String> {
+    let Some(directory) = base_directory(node, base)? else {
+        return Ok(to_houdini_path(path));
+    };
+
+    Ok(match (path.strip_prefix(&directory), base) {
+        (Ok(relative), PathBase::Hip) => format!("$HIP/{}", to_houdini_path(relative)),
+        (Ok(relative), _) => format!(
+            "${}/{}",
+            asset_directory_var(&directory),
+            to_houdini_path(relative)
+        ),
+        (Err(_), _) => to_houdini_path(path),
+    })
+}
@@ -0,0 +1,92 @@
+/// This module works out whether parameters are currently shown and editable.
+///
+/// Houdini re-evaluates hide-when and disable-when conditions whenever a
+/// parameter changes, and reports the result through each parameter's
+/// `invisible` and `disabled` flags. A parameter inside a hidden or disabled
+/// folder is hidden or disabled too, even if its own flags are clear.
+
+use std::collections::HashMap;
+
+use hapi_rs::{node::HoudiniNode, parameter::ParmBaseTrait};
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Debug, Error, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParamStateError {
+    #[error("Failed to get asset parameters")]
+    GetParameters,
+}
+
+type Result<T> = std::result::Result<T, ParamStateError>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParameterState {
+    pub id: i32,
+    pub name: Option<String>,
+    pub visible: bool,
+    pub enabled: bool,
+}
+
+struct RawState {
+    parent_id: i32,
+    name: Option<String>,
+    invisible: bool,
+    disabled: bool,
+}
+
+/// Returns the effective visibility and enabled state of every parameter on
+/// a node, keyed by parameter ID.
+pub(crate) fn read_states(node: &HoudiniNode) -> Result<HashMap<i32, ParameterState>> {
+    let raw: HashMap<i32, RawState> = node
+        .parameters()
+        .map_err(|_| ParamStateError::GetParameters)?
+        .into_iter()
+        .map(|param| {
+            let info = param.info();
+            (
+                info.id().0,
+                RawState {
+                    parent_id: info.parent_id().0,
+                    name: info.name().ok(),
+                    invisible: info.invisible(),
+                    disabled: info.disabled(),
+                },
+            )
+        })
+        .collect();
+
+    let states = raw
+        .iter()
+        .map(|(&id, state)| {
+            let mut visible = !state.invisible;
+            let mut enabled = !state.disabled;
+
+            // Walk up through the containing folders. Parameter layouts are
+            // shallow, but the walk is bounded in case of a malformed parent
+            // chain.
+            let mut parent_id = state.parent_id;
+            for _ in 0..raw.len() {
+                let Some(parent) = raw.get(&parent_id) else {
+                    break;
+                };
+                visible &= !parent.invisible;
+                enabled &= !parent.disabled;
+                parent_id = parent.parent_id;
+            }
+
+            (
+                id,
+                ParameterState {
+                    id,
+                    name: state.name.clone(),
+                    visible,
+                    enabled,
+                },
+            )
+        })
+        .collect();
+
+    Ok(states)
+}
@@ -0,0 +1,82 @@
+/// This module arranges an asset's flat parameter list into the nested layout
+/// shown in Houdini, so clients can render folders, tabs and multiparms
+/// without rebuilding the structure themselves.
+///
+/// Every parameter points at its parent through `parent_id`: folders are
+/// children of their folder list (tabs or radio folders), and the contents of
+/// a folder or multiparm instance are children of it. Parameters with no
+/// parent are at the root.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use super::SerializableParameter;
+
+/// Parent ID used by HAPI for parameters at the root of the interface.
+const ROOT_PARENT_ID: i32 = -1;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParameterNode {
+    #[serde(flatten)]
+    pub parameter: SerializableParameter,
+    /// Child parameters, ordered by `child_index`.
+    pub children: Vec<ParameterNode>,
+}
+
+/// Parameters as a flat list, or arranged into a tree.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum AssetParameters {
+    Flat(Vec<SerializableParameter>),
+    Tree(Vec<ParameterNode>),
+}
+
+fn build_nodes(
+    parent_id: i32,
+    children_by_parent: &mut HashMap<i32, Vec<SerializableParameter>>,
+) -> Vec<ParameterNode> {
+    let Some(children) = children_by_parent.remove(&parent_id) else {
+        return Vec::new();
+    };
+
+    children
+        .into_iter()
+        .map(|parameter| {
+            let children = build_nodes(parameter.info.id, children_by_parent);
+            ParameterNode {
+                parameter,
+                children,
+            }
+        })
+        .collect()
+}
+
+/// Builds the parameter tree from a flat parameter list. Parameters whose
+/// parent is missing from the list are placed at the root.
+pub fn build_parameter_tree(parameters: Vec<SerializableParameter>) -> Vec<ParameterNode> {
+    let ids: HashSet<i32> = parameters
+        .iter()
+        .map(|parameter| parameter.info.id)
+        .collect();
+
+    let mut children_by_parent: HashMap<i32, Vec<SerializableParameter>> = HashMap::new();
+    for parameter in parameters {
+        let parent_id = if ids.contains(&parameter.info.parent_id) {
+            parameter.info.parent_id
+        } else {
+            ROOT_PARENT_ID
+        };
+        children_by_parent
+            .entry(parent_id)
+            .or_default()
+            .push(parameter);
+    }
+
+    for children in children_by_parent.values_mut() {
+        children.sort_by_key(|parameter| parameter.info.child_index);
+    }
+
+    build_nodes(ROOT_PARENT_ID, &mut children_by_parent)
+}
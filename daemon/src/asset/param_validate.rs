@@ -0,0 +1,277 @@
+/// This module checks client-provided parameter values before they are
+/// written, so a bad value is rejected with a reason the client can show next
+/// to the field rather than being passed on to Houdini.
+///
+/// Values are checked against the parameter's type, tuple size, permissions
+/// and hard range. UI ranges are only slider hints and are not enforced. In
+/// clamp mode, out-of-range numbers are moved into range instead of being
+/// rejected.
+///
+/// Writing a value to a parameter driven by an expression replaces the
+/// expression, so such writes are rejected unless the client confirms them.
+
+use hapi_rs::{
+    geometry::{Permissions, RampType},
+    node::HoudiniNode,
+    parameter::{ParmBaseTrait, ParmInfo},
+    session::{Parameter, ParmType},
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    asset_param::ParamValue,
+    param_expression,
+    param_menu::{self, MenuChoice},
+    param_write::ParameterWrite,
+};
+
+/// Client-provided settings for how parameter writes are checked.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WriteOptions {
+    /// Whether to clamp numbers to each parameter's hard range instead of
+    /// rejecting them.
+    pub clamp: bool,
+    /// Whether to replace expressions on the written parameters.
+    pub overwrite_expressions: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "reason", rename_all = "camelCase")]
+pub enum FieldErrorKind {
+    NotFound,
+    ReadOnly,
+    TypeMismatch,
+    /// The parameter is driven by expressions, which the write would replace.
+    HasExpression {
+        expressions: Vec<Option<String>>,
+    },
+    WrongSize {
+        expected: i32,
+        actual: usize,
+    },
+    /// A menu write that sets neither `current` nor `token`.
+    MissingChoice,
+    /// The token isn't one of the menu's choices.
+    InvalidChoice {
+        token: String,
+    },
+    /// The first component of the value that is out of range.
+    OutOfRange {
+        index: usize,
+        value: f32,
+        min: Option<f32>,
+        max: Option<f32>,
+    },
+}
+
+/// Why the write to one parameter was rejected.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldError {
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: FieldErrorKind,
+}
+
+/// Hard limits of a numeric parameter.
+struct Range {
+    min: Option<f32>,
+    max: Option<f32>,
+}
+
+impl Range {
+    fn from_info(info: &ParmInfo) -> Self {
+        Self {
+            min: info.has_min().then(|| info.min()),
+            max: info.has_max().then(|| info.max()),
+        }
+    }
+
+    fn contains(&self, value: f32) -> bool {
+        self.min.map_or(true, |min| value >= min) && self.max.map_or(true, |max| value <= max)
+    }
+
+    fn clamp(&self, value: f32) -> f32 {
+        let value = self.min.map_or(value, |min| value.max(min));
+        self.max.map_or(value, |max| value.min(max))
+    }
+
+    /// Checks every component of a value, clamping them if `clamp` is set.
+    fn check<T: Copy>(
+        &self,
+        values: &[T],
+        clamp: bool,
+        to_f32: impl Fn(T) -> f32,
+        from_f32: impl Fn(f32) -> T,
+    ) -> Result<Vec<T>, FieldErrorKind> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(index, &value)| {
+                let float = to_f32(value);
+                if self.contains(float) {
+                    Ok(value)
+                } else if clamp {
+                    Ok(from_f32(self.clamp(float)))
+                } else {
+                    Err(FieldErrorKind::OutOfRange {
+                        index,
+                        value: float,
+                        min: self.min,
+                        max: self.max,
+                    })
+                }
+            })
+            .collect()
+    }
+}
+
+fn check_size(info: &ParmInfo, actual: usize) -> Result<(), FieldErrorKind> {
+    if actual == info.size() as usize {
+        Ok(())
+    } else {
+        Err(FieldErrorKind::WrongSize {
+            expected: info.size(),
+            actual,
+        })
+    }
+}
+
+/// Checks a menu write against the menu's choices, returning the selected
+/// index, or the token if a string menu accepts values outside its choices.
+fn check_menu(
+    param: &Parameter,
+    choices: &[MenuChoice],
+    current: Option<i32>,
+    token: Option<&String>,
+) -> Result<ParamValue, FieldErrorKind> {
+    let (current, token) = match (current, token) {
+        (Some(index), _) if index < 0 || index as usize >= choices.len() => {
+            return Err(FieldErrorKind::OutOfRange {
+                index: 0,
+                value: index as f32,
+                min: Some(0.0),
+                max: Some(choices.len().saturating_sub(1) as f32),
+            });
+        }
+        (Some(index), _) => (Some(index), None),
+        (None, Some(token)) => match choices.iter().position(|choice| &choice.value == token) {
+            Some(index) => (Some(index as i32), None),
+            None if matches!(param, Parameter::String(_)) && !param_menu::is_strict(param) => {
+                (None, Some(token.clone()))
+            }
+            None => {
+                return Err(FieldErrorKind::InvalidChoice {
+                    token: token.clone(),
+                })
+            }
+        },
+        (None, None) => return Err(FieldErrorKind::MissingChoice),
+    };
+
+    Ok(ParamValue::Menu {
+        choices: Vec::new(),
+        current,
+        token,
+    })
+}
+
+/// Checks one write against its parameter, returning the value to write.
+fn validate_value(
+    param: &Parameter,
+    value: &ParamValue,
+    options: &WriteOptions,
+) -> Result<ParamValue, FieldErrorKind> {
+    let info = param.info();
+    if info.permissions() == Permissions::ReadOnly {
+        return Err(FieldErrorKind::ReadOnly);
+    }
+
+    if !options.overwrite_expressions {
+        let expressions = param_expression::read_expressions(param).unwrap_or_default();
+        if expressions.iter().any(Option::is_some) {
+            return Err(FieldErrorKind::HasExpression { expressions });
+        }
+    }
+
+    let clamp = options.clamp;
+
+    let range = Range::from_info(info);
+    match (param, value) {
+        (Parameter::Float(_), ParamValue::Float { current }) => {
+            check_size(info, current.len())?;
+            let current = range.check(current, clamp, |value| value, |value| value)?;
+            Ok(ParamValue::Float { current })
+        }
+        (Parameter::Int(_), ParamValue::Int { current })
+            if info.parm_type() != ParmType::Toggle =>
+        {
+            check_size(info, current.len())?;
+            let current = range.check(
+                current,
+                clamp,
+                |value| value as f32,
+                |value| value.round() as i32,
+            )?;
+            Ok(ParamValue::Int { current })
+        }
+        (Parameter::Int(_), ParamValue::Toggle { .. }) if info.parm_type() == ParmType::Toggle => {
+            Ok(value.clone())
+        }
+        (Parameter::Int(_), ParamValue::Ramp { .. }) if info.ramp_type() != RampType::Invalid => {
+            Ok(value.clone())
+        }
+        (Parameter::String(_), ParamValue::String { current }) => {
+            check_size(info, current.len())?;
+            Ok(value.clone())
+        }
+        (Parameter::Int(_) | Parameter::String(_), ParamValue::Menu { current, token, .. })
+            if info.choice_count() > 0 =>
+        {
+            match param_menu::read_choices(param) {
+                Ok(Some(choices)) => check_menu(param, &choices, *current, token.as_ref()),
+                Ok(None) => Err(FieldErrorKind::TypeMismatch),
+                // The write reports the failure to read the choices.
+                Err(_) => Ok(value.clone()),
+            }
+        }
+        _ => Err(FieldErrorKind::TypeMismatch),
+    }
+}
+
+/// Checks every write before any of them is applied. Returns the writes to
+/// apply, with values clamped if requested, or one error per rejected
+/// parameter.
+pub(crate) fn validate_writes(
+    node: &HoudiniNode,
+    writes: &[ParameterWrite],
+    options: &WriteOptions,
+) -> Result<Vec<ParameterWrite>, Vec<FieldError>> {
+    let mut validated = Vec::with_capacity(writes.len());
+    let mut errors = Vec::new();
+
+    for write in writes {
+        let result = match node.parameter(&write.name) {
+            Ok(param) => validate_value(&param, &write.value, options),
+            Err(_) => Err(FieldErrorKind::NotFound),
+        };
+
+        match result {
+            Ok(value) => validated.push(ParameterWrite {
+                name: write.name.clone(),
+                value,
+            }),
+            Err(kind) => errors.push(FieldError {
+                name: write.name.clone(),
+                kind,
+            }),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(validated)
+    } else {
+        Err(errors)
+    }
+}
@@ -0,0 +1,101 @@
+/// This module writes client-provided values to asset parameters.
+
+use hapi_rs::{node::HoudiniNode, session::Parameter};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    asset_param::ParamValue,
+    param_menu::{self, MenuChoice},
+    ramp::{self, RampError, RampInfo},
+};
+
+#[derive(Debug, Error, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParamWriteError {
+    #[error("Asset has no parameter named {0}")]
+    ParameterNotFound(String),
+    #[error("Parameter {0} can't be set to this type of value")]
+    TypeMismatch(String),
+    #[error("Failed to set parameter {0}")]
+    SetValue(String),
+    #[error("Failed to set ramp {0}")]
+    SetRamp(String, RampError),
+    #[error("Failed to read menu choices of parameter {0}")]
+    ReadChoices(String),
+    #[error("Menu {0} has no such choice")]
+    InvalidChoice(String),
+}
+
+type Result<T> = std::result::Result<T, ParamWriteError>;
+
+/// A new value for a parameter, identified by name.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParameterWrite {
+    pub name: String,
+    pub value: ParamValue,
+}
+
+fn menu_choices(name: &str, param: &Parameter) -> Result<Vec<MenuChoice>> {
+    param_menu::read_choices(param)
+        .map_err(|_| ParamWriteError::ReadChoices(name.to_owned()))?
+        .ok_or_else(|| ParamWriteError::TypeMismatch(name.to_owned()))
+}
+
+pub(crate) fn write_parameter(node: &HoudiniNode, write: &ParameterWrite) -> Result<()> {
+    let param = node
+        .parameter(&write.name)
+        .map_err(|_| ParamWriteError::ParameterNotFound(write.name.clone()))?;
+    let set_failed = |_| ParamWriteError::SetValue(write.name.clone());
+
+    match (&param, &write.value) {
+        (Parameter::Float(param), ParamValue::Float { current }) => {
+            param.set_array(current.as_slice()).map_err(set_failed)
+        }
+        (Parameter::Int(param), ParamValue::Int { current }) => {
+            param.set_array(current.as_slice()).map_err(set_failed)
+        }
+        (Parameter::Int(param), ParamValue::Toggle { current }) => {
+            param.set(0, *current as i32).map_err(set_failed)
+        }
+        (Parameter::Int(int_param), ParamValue::Menu { current, token, .. }) => {
+            let index = match (current, token) {
+                (Some(index), _) => *index,
+                (None, Some(token)) => menu_choices(&write.name, &param)?
+                    .iter()
+                    .position(|choice| &choice.value == token)
+                    .ok_or_else(|| ParamWriteError::InvalidChoice(write.name.clone()))?
+                    as i32,
+                (None, None) => return Err(ParamWriteError::InvalidChoice(write.name.clone())),
+            };
+            int_param.set(0, index).map_err(set_failed)
+        }
+        (Parameter::String(string_param), ParamValue::Menu { current, token, .. }) => {
+            let choices = menu_choices(&write.name, &param)?;
+            let value = match (current, token) {
+                (Some(index), _) => usize::try_from(*index)
+                    .ok()
+                    .and_then(|index| choices.get(index))
+                    .map(|choice| choice.value.clone()),
+                (None, Some(token)) => {
+                    let is_choice = choices.iter().any(|choice| &choice.value == token);
+                    (is_choice || !param_menu::is_strict(&param)).then(|| token.clone())
+                }
+                (None, None) => None,
+            }
+            .ok_or_else(|| ParamWriteError::InvalidChoice(write.name.clone()))?;
+            string_param.set(0, value.as_str()).map_err(set_failed)
+        }
+        (Parameter::String(param), ParamValue::String { current }) => {
+            param.set_array(current.as_slice()).map_err(set_failed)
+        }
+        (Parameter::Int(_), ParamValue::Ramp { keys }) => {
+            let ramp_info = RampInfo::from_parm(param.info())
+                .ok_or_else(|| ParamWriteError::TypeMismatch(write.name.clone()))?;
+            ramp::write_ramp(node, &ramp_info, keys)
+                .map_err(|error| ParamWriteError::SetRamp(write.name.clone(), error))
+        }
+        _ => Err(ParamWriteError::TypeMismatch(write.name.clone())),
+    }
+}
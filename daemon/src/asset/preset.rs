@@ -0,0 +1,104 @@
+/// This module captures and restores named sets of parameter values.
+///
+/// Values are keyed by parameter name, so a preset still applies after the
+/// HDA is edited. Parameters that no longer exist are skipped. Multiparm
+/// counts are restored before anything else, since the parameters of their
+/// instances only exist once the instances do.
+///
+/// Presets leave expressions alone, while snapshots also record them so a
+/// set of parameters can be put back exactly as it was.
+
+use std::collections::BTreeMap;
+
+use hapi_rs::{node::HoudiniNode, parameter::ParmBaseTrait, session::ParmType};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    asset_param::{ParamValue, Permissions, SerializableParameter},
+    param_state::ParameterState,
+};
+
+/// Parameter values keyed by parameter name.
+pub type PresetValues = BTreeMap<String, ParamValue>;
+
+/// Expression of each component of a parameter, keyed by parameter name.
+pub type PresetExpressions = BTreeMap<String, Vec<Option<String>>>;
+
+/// Values and expressions of a set of parameters.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParameterSnapshot {
+    /// Values of every writable parameter, including the current values of
+    /// those driven by expressions.
+    pub values: PresetValues,
+    /// Expressions of the parameters driven by them.
+    #[serde(default)]
+    pub expressions: PresetExpressions,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppliedPreset {
+    /// Effective visibility and enabled state of every parameter that was
+    /// written or whose state changed.
+    pub states: Vec<ParameterState>,
+    /// Parameters in the preset that the asset doesn't have, or whose values
+    /// don't fit them.
+    pub skipped: Vec<String>,
+}
+
+/// Returns the name and value of a writable parameter.
+fn writable_value(parameter: &SerializableParameter) -> Option<(String, ParamValue)> {
+    if matches!(parameter.info.permissions, Permissions::ReadOnly) {
+        return None;
+    }
+
+    let name = parameter.info.name.clone()?;
+    let value = match &parameter.current_value {
+        ParamValue::NoDefault => return None,
+        // Menus are restored by token where possible, which survives choices
+        // being reordered.
+        ParamValue::Menu { current, token, .. } => ParamValue::Menu {
+            choices: Vec::new(),
+            current: if token.is_some() { None } else { *current },
+            token: token.clone(),
+        },
+        value => value.clone(),
+    };
+    Some((name, value))
+}
+
+/// Returns the values of every writable parameter. Parameters driven by
+/// expressions are left out, so applying the preset keeps the expressions.
+pub(crate) fn capture(parameters: Vec<SerializableParameter>) -> PresetValues {
+    parameters
+        .iter()
+        .filter(|parameter| parameter.expressions.iter().all(Option::is_none))
+        .filter_map(writable_value)
+        .collect()
+}
+
+/// Returns the values and expressions of every writable parameter.
+pub(crate) fn snapshot(parameters: Vec<SerializableParameter>) -> ParameterSnapshot {
+    let mut snapshot = ParameterSnapshot::default();
+    for parameter in parameters {
+        let Some((name, value)) = writable_value(&parameter) else {
+            continue;
+        };
+
+        if parameter.expressions.iter().any(Option::is_some) {
+            snapshot
+                .expressions
+                .insert(name.clone(), parameter.expressions);
+        }
+        snapshot.values.insert(name, value);
+    }
+    snapshot
+}
+
+/// Returns whether a parameter is a multiparm, including ramps.
+pub(crate) fn is_multiparm(node: &HoudiniNode, name: &str) -> bool {
+    node.parameter(name).map_or(false, |param| {
+        param.info().parm_type() == ParmType::Multiparmlist
+    })
+}
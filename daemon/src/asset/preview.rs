@@ -0,0 +1,238 @@
+/// This module renders small preview images of cooked meshes for the asset
+/// browser. Rendering happens entirely on the CPU with a simple z-buffered
+/// rasterizer, so it also works on headless build machines.
+///
+/// Meshes are drawn with an orthographic camera framed around their bounds,
+/// flat two-sided lambert shading and interpolated vertex colors.
+
+use glam::{Mat4, Vec2, Vec3};
+use resvg::tiny_skia::{IntSize, Pixmap};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::MeshData;
+
+/// Each pixel is rendered from this many samples per axis, to smooth edges.
+const SUPERSAMPLING: u32 = 2;
+/// Fraction of the image left empty around the mesh on each side.
+const MARGIN: f32 = 0.08;
+const MAX_SIZE: u32 = 1024;
+
+const AMBIENT: f32 = 0.25;
+const DEFAULT_COLOR: Vec3 = Vec3::splat(0.8);
+
+#[derive(Debug, Error, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreviewError {
+    #[error("Preview size must be between 1 and {MAX_SIZE}")]
+    InvalidSize,
+    #[error("Mesh has no triangles to preview")]
+    EmptyMesh,
+    #[error("Failed to encode preview image")]
+    EncodePng,
+}
+
+type Result<T> = std::result::Result<T, PreviewError>;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PreviewView {
+    /// Looking straight at the front of the mesh.
+    Front,
+    /// Looking down at the front-right corner of the mesh.
+    ThreeQuarter,
+}
+
+impl PreviewView {
+    fn view_matrix(self) -> Mat4 {
+        let eye = match self {
+            Self::Front => Vec3::Z,
+            Self::ThreeQuarter => Vec3::new(1.0, 0.75, 1.0).normalize(),
+        };
+        Mat4::look_at_rh(eye, Vec3::ZERO, Vec3::Y)
+    }
+}
+
+/// Client-provided settings for the cook preview.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PreviewOptions {
+    pub view: PreviewView,
+    /// Width and height of the image in pixels.
+    pub size: u32,
+}
+
+impl Default for PreviewOptions {
+    fn default() -> Self {
+        Self {
+            view: PreviewView::ThreeQuarter,
+            size: 256,
+        }
+    }
+}
+
+/// A corner projected to screen space. `z` grows towards the camera.
+#[derive(Clone, Copy)]
+struct ScreenCorner {
+    position: Vec2,
+    z: f32,
+    color: Vec3,
+}
+
+/// Premultiplied RGBA color and depth buffers.
+struct FrameBuffer {
+    size: u32,
+    color: Vec<[f32; 4]>,
+    depth: Vec<f32>,
+}
+
+impl FrameBuffer {
+    fn new(size: u32) -> Self {
+        let pixel_count = (size * size) as usize;
+        Self {
+            size,
+            color: vec![[0.0; 4]; pixel_count],
+            depth: vec![f32::NEG_INFINITY; pixel_count],
+        }
+    }
+
+    fn draw_triangle(&mut self, corners: [ScreenCorner; 3], shade: f32) {
+        let [a, b, c] = corners;
+        let edge = |from: Vec2, to: Vec2, point: Vec2| (to - from).perp_dot(point - from);
+
+        let area = edge(a.position, b.position, c.position);
+        if area.abs() <= f32::EPSILON {
+            return;
+        }
+
+        let min = a
+            .position
+            .min(b.position)
+            .min(c.position)
+            .floor()
+            .max(Vec2::ZERO);
+        let max = a
+            .position
+            .max(b.position)
+            .max(c.position)
+            .ceil()
+            .min(Vec2::splat(self.size as f32 - 1.0));
+
+        for y in min.y as u32..=max.y as u32 {
+            for x in min.x as u32..=max.x as u32 {
+                let point = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+
+                // Barycentric weights, which are all positive inside the
+                // triangle whichever way it is wound.
+                let wa = edge(b.position, c.position, point) / area;
+                let wb = edge(c.position, a.position, point) / area;
+                let wc = edge(a.position, b.position, point) / area;
+                if wa < 0.0 || wb < 0.0 || wc < 0.0 {
+                    continue;
+                }
+
+                let index = (y * self.size + x) as usize;
+                let z = a.z * wa + b.z * wb + c.z * wc;
+                if z <= self.depth[index] {
+                    continue;
+                }
+                self.depth[index] = z;
+
+                let color = (a.color * wa + b.color * wb + c.color * wc)
+                    .clamp(Vec3::ZERO, Vec3::ONE)
+                    * shade;
+                self.color[index] = [color.x, color.y, color.z, 1.0];
+            }
+        }
+    }
+
+    /// Averages each block of samples into one pixel and encodes the result.
+    fn resolve_png(&self, samples: u32) -> Result<Vec<u8>> {
+        let size = self.size / samples;
+        let weight = 1.0 / (samples * samples) as f32;
+
+        let mut data = Vec::with_capacity((size * size * 4) as usize);
+        for y in 0..size {
+            for x in 0..size {
+                let mut pixel = [0.0; 4];
+                for sy in 0..samples {
+                    for sx in 0..samples {
+                        let index = ((y * samples + sy) * self.size + x * samples + sx) as usize;
+                        for (channel, value) in pixel.iter_mut().zip(self.color[index]) {
+                            *channel += value * weight;
+                        }
+                    }
+                }
+                data.extend(pixel.map(|channel| (channel * 255.0).round() as u8));
+            }
+        }
+
+        let size = IntSize::from_wh(size, size).ok_or(PreviewError::InvalidSize)?;
+        Pixmap::from_vec(data, size)
+            .ok_or(PreviewError::EncodePng)?
+            .encode_png()
+            .map_err(|_| PreviewError::EncodePng)
+    }
+}
+
+/// Renders a mesh to a square PNG.
+pub(crate) fn render_preview(mesh: &MeshData, options: &PreviewOptions) -> Result<Vec<u8>> {
+    if options.size == 0 || options.size > MAX_SIZE {
+        return Err(PreviewError::InvalidSize);
+    }
+
+    let view = options.view.view_matrix();
+    let corners: Vec<(Vec3, Vec3)> = mesh
+        .corners()
+        .map(|(position, color)| {
+            (
+                view.transform_point3(position),
+                color.unwrap_or(DEFAULT_COLOR),
+            )
+        })
+        .collect();
+    if corners.len() < 3 {
+        return Err(PreviewError::EmptyMesh);
+    }
+
+    // Frame the mesh so its larger side fills the image, minus the margin.
+    let (min, max) = corners.iter().fold(
+        (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+        |(min, max), (position, _)| (min.min(position.truncate()), max.max(position.truncate())),
+    );
+    let center = (min + max) / 2.0;
+    let extent = (max - min).max_element().max(f32::EPSILON);
+
+    let buffer_size = options.size * SUPERSAMPLING;
+    let scale = buffer_size as f32 * (1.0 - MARGIN * 2.0) / extent;
+    let half_size = buffer_size as f32 / 2.0;
+
+    let light = Vec3::new(-0.4, 0.6, 1.0).normalize();
+
+    let mut frame = FrameBuffer::new(buffer_size);
+    for triangle in corners.chunks_exact(3) {
+        let [(a, _), (b, _), (c, _)] = [triangle[0], triangle[1], triangle[2]];
+        let normal = (b - a).cross(c - a).normalize_or_zero();
+        let shade = AMBIENT + (1.0 - AMBIENT) * normal.dot(light).abs();
+
+        let project = |(position, color): (Vec3, Vec3)| ScreenCorner {
+            position: Vec2::new(
+                half_size + (position.x - center.x) * scale,
+                half_size - (position.y - center.y) * scale,
+            ),
+            z: position.z,
+            color,
+        };
+
+        frame.draw_triangle(
+            [
+                project(triangle[0]),
+                project(triangle[1]),
+                project(triangle[2]),
+            ],
+            shade,
+        );
+    }
+
+    frame.resolve_png(SUPERSAMPLING)
+}
@@ -0,0 +1,199 @@
+/// This module reads and writes ramp parameters as ordered lists of keys.
+///
+/// HAPI exposes a ramp as a multiparm whose value is the number of keys. Each
+/// key is a multiparm instance with `<name><n>pos`, `<name><n>value` (float
+/// ramps) or `<name><n>c` (color ramps), and `<name><n>interp` parameters,
+/// where `n` counts up from the ramp's instance start offset.
+
+use hapi_rs::{geometry::RampType, node::HoudiniNode, parameter::ParmInfo, session::Parameter};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Error, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RampError {
+    #[error("Failed to read ramp parameter {0}")]
+    ReadKey(String),
+    #[error("Failed to write ramp parameter {0}")]
+    WriteKey(String),
+    #[error("Float ramps need float values and color ramps need color values")]
+    ValueTypeMismatch,
+}
+
+type Result<T> = std::result::Result<T, RampError>;
+
+/// Interpolation between a key and the next one. The discriminants match the
+/// entries of Houdini's interpolation menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RampBasis {
+    Constant = 0,
+    Linear = 1,
+    CatmullRom = 2,
+    MonotoneCubic = 3,
+    Bezier = 4,
+    BSpline = 5,
+    Hermite = 6,
+}
+
+impl From<i32> for RampBasis {
+    fn from(value: i32) -> Self {
+        match value {
+            0 => Self::Constant,
+            2 => Self::CatmullRom,
+            3 => Self::MonotoneCubic,
+            4 => Self::Bezier,
+            5 => Self::BSpline,
+            6 => Self::Hermite,
+            _ => Self::Linear,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RampValue {
+    Float(f32),
+    Color([f32; 3]),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RampKey {
+    pub position: f32,
+    pub value: RampValue,
+    pub basis: RampBasis,
+}
+
+/// Identifies a ramp parameter and how its keys are named.
+pub(crate) struct RampInfo {
+    name: String,
+    start_offset: i32,
+    is_color: bool,
+}
+
+impl RampInfo {
+    /// Returns `None` if the parameter isn't a ramp.
+    pub fn from_parm(info: &ParmInfo) -> Option<Self> {
+        let is_color = match info.ramp_type() {
+            RampType::Float => false,
+            RampType::Color => true,
+            _ => return None,
+        };
+
+        Some(Self {
+            name: info.name().ok()?,
+            start_offset: info.instance_start_offset(),
+            is_color,
+        })
+    }
+
+    fn key_parm(&self, key: i32, suffix: &str) -> String {
+        format!("{}{}{suffix}", self.name, self.start_offset + key)
+    }
+
+    fn value_suffix(&self) -> &'static str {
+        if self.is_color {
+            "c"
+        } else {
+            "value"
+        }
+    }
+}
+
+fn read_floats(node: &HoudiniNode, name: &str) -> Result<Vec<f32>> {
+    match node.parameter(name) {
+        Ok(Parameter::Float(param)) => param
+            .get_array()
+            .map_err(|_| RampError::ReadKey(name.to_owned())),
+        _ => Err(RampError::ReadKey(name.to_owned())),
+    }
+}
+
+fn read_int(node: &HoudiniNode, name: &str) -> Result<i32> {
+    match node.parameter(name) {
+        Ok(Parameter::Int(param)) => param
+            .get(0)
+            .map_err(|_| RampError::ReadKey(name.to_owned())),
+        _ => Err(RampError::ReadKey(name.to_owned())),
+    }
+}
+
+fn write_floats(node: &HoudiniNode, name: &str, values: &[f32]) -> Result<()> {
+    match node.parameter(name) {
+        Ok(Parameter::Float(param)) => param
+            .set_array(values)
+            .map_err(|_| RampError::WriteKey(name.to_owned())),
+        _ => Err(RampError::WriteKey(name.to_owned())),
+    }
+}
+
+fn write_int(node: &HoudiniNode, name: &str, value: i32) -> Result<()> {
+    match node.parameter(name) {
+        Ok(Parameter::Int(param)) => param
+            .set(0, value)
+            .map_err(|_| RampError::WriteKey(name.to_owned())),
+        _ => Err(RampError::WriteKey(name.to_owned())),
+    }
+}
+
+/// Reads the keys of a ramp with `count` keys, ordered by position.
+pub(crate) fn read_ramp(node: &HoudiniNode, ramp: &RampInfo, count: i32) -> Result<Vec<RampKey>> {
+    let mut keys = Vec::with_capacity(count.max(0) as usize);
+    for key in 0..count {
+        let position = read_floats(node, &ramp.key_parm(key, "pos"))?
+            .first()
+            .copied()
+            .unwrap_or_default();
+
+        let values = read_floats(node, &ramp.key_parm(key, ramp.value_suffix()))?;
+        let value = match values[..] {
+            [r, g, b, ..] if ramp.is_color => RampValue::Color([r, g, b]),
+            [value, ..] if !ramp.is_color => RampValue::Float(value),
+            _ => return Err(RampError::ReadKey(ramp.key_parm(key, ramp.value_suffix()))),
+        };
+
+        let basis = read_int(node, &ramp.key_parm(key, "interp"))?.into();
+
+        keys.push(RampKey {
+            position,
+            value,
+            basis,
+        });
+    }
+
+    keys.sort_by(|a, b| a.position.total_cmp(&b.position));
+    Ok(keys)
+}
+
+/// Replaces every key of a ramp. Setting the ramp's value changes how many
+/// keys it has, after which each key is written in order of position.
+pub(crate) fn write_ramp(node: &HoudiniNode, ramp: &RampInfo, keys: &[RampKey]) -> Result<()> {
+    let value_matches = |key: &RampKey| match key.value {
+        RampValue::Float(_) => !ramp.is_color,
+        RampValue::Color(_) => ramp.is_color,
+    };
+    if !keys.iter().all(value_matches) {
+        return Err(RampError::ValueTypeMismatch);
+    }
+
+    let mut keys = keys.to_vec();
+    keys.sort_by(|a, b| a.position.total_cmp(&b.position));
+
+    write_int(node, &ramp.name, keys.len() as i32)?;
+
+    for (index, key) in keys.iter().enumerate() {
+        let index = index as i32;
+        write_floats(node, &ramp.key_parm(index, "pos"), &[key.position])?;
+
+        let value_parm = ramp.key_parm(index, ramp.value_suffix());
+        match &key.value {
+            RampValue::Float(value) => write_floats(node, &value_parm, &[*value])?,
+            RampValue::Color(color) => write_floats(node, &value_parm, color)?,
+        }
+
+        write_int(node, &ramp.key_parm(index, "interp"), key.basis as i32)?;
+    }
+
+    Ok(())
+}
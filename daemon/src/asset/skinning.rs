@@ -0,0 +1,311 @@
+/// This module extracts a bone hierarchy and per-vertex skin weights from
+/// Houdini capture attributes, in a layout that maps onto Roblox `Bone`
+/// instances and `EditableMesh` vertex bone weights.
+///
+/// Capture data is read from the standard capture attributes written by
+/// Houdini's capture SOPs and KineFX:
+///
+/// - `boneCapture` (point): Pairs of bone index and weight.
+/// - `boneCapture_pCaptPath` (detail): Path of every captured bone. Path
+///   segments describe the hierarchy, so `root/spine/chest` is parented to
+///   `root/spine`. Parents that are not captured are still emitted as bones.
+/// - `boneCapture_pCaptData` (detail): 20 floats per bone, the first 16 of
+///   which are the inverse of the bone's rest transform.
+
+use std::collections::HashMap;
+
+use glam::{Mat3, Mat4};
+use hapi_rs::{
+    attribute::{NumericArrayAttr, NumericAttr},
+    node::Geometry,
+    session::{AttributeOwner, StorageType},
+};
+use serde::Serialize;
+use thiserror::Error;
+
+use super::{
+    attribute_data::{read_raw_attribute, AttributeDataError, AttributeValues, TriangleIndices},
+    mesh_data::GeometrySource,
+};
+
+const CAPTURE_ATTRIBUTE: &str = "boneCapture";
+const CAPTURE_PATH_ATTRIBUTE: &str = "boneCapture_pCaptPath";
+const CAPTURE_DATA_ATTRIBUTE: &str = "boneCapture_pCaptData";
+
+/// Number of floats stored per bone in `boneCapture_pCaptData`.
+const CAPTURE_DATA_STRIDE: usize = 20;
+
+/// Roblox supports at most this many bone influences per vertex.
+pub const MAX_INFLUENCES: usize = 4;
+
+#[derive(Debug, Error, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkinningError {
+    #[error("Geometry has no boneCapture attribute")]
+    NoCaptureAttribute,
+    #[error("Failed to read boneCapture attribute")]
+    ReadCaptureAttribute,
+    #[error("boneCapture attribute has an unsupported storage type")]
+    UnsupportedCaptureStorage,
+    #[error("Failed to read capture attribute")]
+    ReadAttribute(AttributeDataError),
+    #[error("Attribute {0} is missing or has the wrong type")]
+    InvalidCaptureAttribute(&'static str),
+}
+
+type Result<T> = std::result::Result<T, SkinningError>;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoneDescription {
+    pub name: String,
+    /// Index of the parent bone, or `None` for root bones.
+    pub parent: Option<usize>,
+    /// Rest transform relative to the parent bone, as the 12 components
+    /// accepted by `CFrame.new`.
+    pub cframe: [f32; 12],
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkinningData {
+    pub bones: Vec<BoneDescription>,
+    /// Four bone indices per emitted triangle corner, in the same order as the
+    /// mesh `vertexArray`. Unused influences are `-1`.
+    pub bone_indices: Vec<i32>,
+    /// Four normalized weights per emitted triangle corner, matching
+    /// `bone_indices`.
+    pub bone_weights: Vec<f32>,
+}
+
+fn to_cframe(transform: Mat4) -> [f32; 12] {
+    let (_, rotation, translation) = transform.to_scale_rotation_translation();
+    let r = Mat3::from_quat(rotation);
+
+    [
+        translation.x,
+        translation.y,
+        translation.z,
+        r.x_axis.x,
+        r.y_axis.x,
+        r.z_axis.x,
+        r.x_axis.y,
+        r.y_axis.y,
+        r.z_axis.y,
+        r.x_axis.z,
+        r.y_axis.z,
+        r.z_axis.z,
+    ]
+}
+
+/// Keeps the strongest `MAX_INFLUENCES` influences and normalizes their
+/// weights so they sum to one.
+fn top_influences(
+    mut influences: Vec<(i32, f32)>,
+) -> ([i32; MAX_INFLUENCES], [f32; MAX_INFLUENCES]) {
+    influences.retain(|(index, weight)| *index >= 0 && *weight > 0.0);
+    influences.sort_by(|a, b| b.1.total_cmp(&a.1));
+    influences.truncate(MAX_INFLUENCES);
+
+    let total: f32 = influences.iter().map(|(_, weight)| weight).sum();
+
+    let mut indices = [-1; MAX_INFLUENCES];
+    let mut weights = [0.0; MAX_INFLUENCES];
+    for (slot, (index, weight)) in influences.into_iter().enumerate() {
+        indices[slot] = index;
+        weights[slot] = weight / total;
+    }
+
+    (indices, weights)
+}
+
+/// Reads the per-point capture influences as (bone index, weight) pairs.
+fn read_point_influences(geo: &Geometry, part_id: i32) -> Result<Vec<Vec<(i32, f32)>>> {
+    let attr = geo
+        .get_attribute(part_id, AttributeOwner::Point, CAPTURE_ATTRIBUTE)
+        .map_err(|_| SkinningError::ReadCaptureAttribute)?
+        .ok_or(SkinningError::NoCaptureAttribute)?;
+
+    let to_pairs = |values: &[f32]| -> Vec<(i32, f32)> {
+        values
+            .chunks_exact(2)
+            .map(|pair| (pair[0] as i32, pair[1]))
+            .collect()
+    };
+
+    match attr.storage() {
+        StorageType::FloatArray => {
+            let data = attr
+                .downcast::<NumericArrayAttr<f32>>()
+                .expect("boneCapture is NumericArrayAttribute")
+                .get(part_id)
+                .map_err(|_| SkinningError::ReadCaptureAttribute)?;
+            Ok(data.iter().map(to_pairs).collect())
+        }
+        StorageType::Float => {
+            let tuple_size = attr.info().tuple_size() as usize;
+            let data = attr
+                .downcast::<NumericAttr<f32>>()
+                .expect("boneCapture is NumericAttribute")
+                .get(part_id)
+                .map_err(|_| SkinningError::ReadCaptureAttribute)?;
+            Ok(data.chunks_exact(tuple_size).map(to_pairs).collect())
+        }
+        _ => Err(SkinningError::UnsupportedCaptureStorage),
+    }
+}
+
+/// Builds the bone list from capture paths and their inverse rest transforms.
+/// Returns the bones and the index of every capture path in the bone list.
+fn build_bones(paths: &[String], capture_data: &[f32]) -> (Vec<BoneDescription>, Vec<usize>) {
+    let mut world_transforms: HashMap<&str, Mat4> = HashMap::new();
+    for (index, path) in paths.iter().enumerate() {
+        let start = index * CAPTURE_DATA_STRIDE;
+        let Some(data) = capture_data.get(start..start + 16) else {
+            continue;
+        };
+        // Houdini matrices are row-major with row vectors, which is the same
+        // memory layout as a column-major matrix with column vectors.
+        let inverse_rest = Mat4::from_cols_slice(data);
+        world_transforms.insert(path.trim_matches('/'), inverse_rest.inverse());
+    }
+
+    let mut bones: Vec<BoneDescription> = Vec::new();
+    let mut bone_worlds: Vec<Mat4> = Vec::new();
+    let mut bone_lookup: HashMap<String, usize> = HashMap::new();
+
+    let mut capture_indices = Vec::with_capacity(paths.len());
+    for path in paths {
+        let path = path.trim_matches('/');
+
+        // Make sure every ancestor exists before the bone itself.
+        let mut parent: Option<usize> = None;
+        let mut current = String::new();
+        for segment in path.split('/') {
+            if !current.is_empty() {
+                current.push('/');
+            }
+            current.push_str(segment);
+
+            if let Some(&index) = bone_lookup.get(&current) {
+                parent = Some(index);
+                continue;
+            }
+
+            let parent_world = parent.map_or(Mat4::IDENTITY, |parent| bone_worlds[parent]);
+            // Bones without a rest transform sit on their parent.
+            let world = world_transforms
+                .get(current.as_str())
+                .copied()
+                .unwrap_or(parent_world);
+
+            let index = bones.len();
+            bones.push(BoneDescription {
+                name: segment.to_owned(),
+                parent,
+                cframe: to_cframe(parent_world.inverse() * world),
+            });
+            bone_worlds.push(world);
+            bone_lookup.insert(current.clone(), index);
+            parent = Some(index);
+        }
+
+        capture_indices.push(parent.expect("capture path has at least one segment"));
+    }
+
+    (bones, capture_indices)
+}
+
+impl SkinningData {
+    /// Converts bone positions from Houdini units to studs.
+    pub(crate) fn convert_to_studs(&mut self, units_per_stud: f32) {
+        for bone in &mut self.bones {
+            for component in &mut bone.cframe[..3] {
+                *component /= units_per_stud;
+            }
+        }
+    }
+
+    /// Reads bones and skin weights for the vertices of a mesh built with the
+    /// same `prim_mask`, so they line up with its vertex array.
+    pub(crate) fn from_source(
+        geo: &Geometry,
+        source: &GeometrySource,
+        prim_mask: Option<&[bool]>,
+    ) -> Result<Self> {
+        let part_id = source.partition.part_id();
+
+        let paths =
+            match read_raw_attribute(geo, part_id, AttributeOwner::Detail, CAPTURE_PATH_ATTRIBUTE)
+                .map_err(SkinningError::ReadAttribute)?
+            {
+                Some(raw) => match raw.values {
+                    AttributeValues::String(paths) => paths,
+                    _ => {
+                        return Err(SkinningError::InvalidCaptureAttribute(
+                            CAPTURE_PATH_ATTRIBUTE,
+                        ))
+                    }
+                },
+                None => {
+                    return Err(SkinningError::InvalidCaptureAttribute(
+                        CAPTURE_PATH_ATTRIBUTE,
+                    ))
+                }
+            };
+
+        let capture_data =
+            match read_raw_attribute(geo, part_id, AttributeOwner::Detail, CAPTURE_DATA_ATTRIBUTE)
+                .map_err(SkinningError::ReadAttribute)?
+            {
+                Some(raw) => match raw.values {
+                    AttributeValues::Float(data) => data,
+                    _ => {
+                        return Err(SkinningError::InvalidCaptureAttribute(
+                            CAPTURE_DATA_ATTRIBUTE,
+                        ))
+                    }
+                },
+                // Without rest transforms every bone sits at the origin.
+                None => Vec::new(),
+            };
+
+        let (bones, capture_indices) = build_bones(&paths, &capture_data);
+
+        let point_influences: Vec<([i32; MAX_INFLUENCES], [f32; MAX_INFLUENCES])> =
+            read_point_influences(geo, part_id)?
+                .into_iter()
+                .map(|influences| {
+                    // Remap capture indices to bone indices.
+                    let influences = influences
+                        .into_iter()
+                        .map(|(index, weight)| {
+                            let bone = capture_indices
+                                .get(index as usize)
+                                .map_or(-1, |bone| *bone as i32);
+                            (if index < 0 { -1 } else { bone }, weight)
+                        })
+                        .collect();
+                    top_influences(influences)
+                })
+                .collect();
+
+        let triangles = TriangleIndices::new(&source.face_counts, &source.vertex_list, prim_mask);
+        let mut bone_indices = Vec::with_capacity(triangles.points.len() * MAX_INFLUENCES);
+        let mut bone_weights = Vec::with_capacity(triangles.points.len() * MAX_INFLUENCES);
+        for &point in &triangles.points {
+            let (indices, weights) = point_influences
+                .get(point)
+                .copied()
+                .unwrap_or(([-1; MAX_INFLUENCES], [0.0; MAX_INFLUENCES]));
+            bone_indices.extend_from_slice(&indices);
+            bone_weights.extend_from_slice(&weights);
+        }
+
+        Ok(Self {
+            bones,
+            bone_indices,
+            bone_weights,
+        })
+    }
+}
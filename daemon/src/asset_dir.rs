@@ -8,7 +8,14 @@ use nanoid::nanoid;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::state::get_config_dir;
+use crate::{
+    asset::PresetValues,
+    asset_images::{render_png, AssetImageKind, ICON_SIZE, THUMBNAIL_SIZE},
+    library_index::{
+        read_definition_images, read_library_index, DefinitionImages, DefinitionMetadata,
+    },
+    state::get_config_dir,
+};
 
 #[derive(Debug, Error, Serialize)]
 #[serde(rename_all = "snake_case")]
@@ -21,6 +28,12 @@ pub enum AssetDirError {
     BadParent(String),
     #[error("The file {0} does not exist")]
     FileDoesNotExist(String),
+    #[error("The file {0} is not an asset")]
+    NotAnAsset(String),
+    #[error("The asset has no preset named {0}")]
+    PresetNotFound(String),
+    #[error("The asset already has a preset named {0}")]
+    PresetExists(String),
 }
 
 type Result<T> = std::result::Result<T, AssetDirError>;
@@ -93,6 +106,55 @@ pub struct AssetConfig {
     /// The asset file could have been deleted or moved, so this field is used
     /// to indicate whether the asset file still exists.
     pub asset_exists: bool,
+    /// The asset definition to instantiate from a library containing several.
+    /// If not set, the first definition in the library is used.
+    #[serde(default)]
+    pub definition: Option<String>,
+    /// Metadata read from the asset library, cached until the file changes.
+    #[serde(default)]
+    pub library: Option<LibraryMetadata>,
+    /// Named parameter sets saved by artists, in the order they were created.
+    #[serde(default)]
+    pub presets: Vec<ParameterPreset>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParameterPreset {
+    pub name: String,
+    pub date_modified: DateTime<Utc>,
+    pub values: PresetValues,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryMetadata {
+    pub read_at: DateTime<Utc>,
+    pub definitions: Vec<DefinitionMetadata>,
+    /// Why the library couldn't be read, if it couldn't. The failure is
+    /// cached like the metadata, so it's only retried once the file changes.
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Reads the metadata of an asset library. Failures are logged and recorded
+/// rather than returned, since the asset can still be loaded without it.
+fn read_library_metadata(asset_path: &Path) -> LibraryMetadata {
+    match read_library_index(asset_path) {
+        Ok(definitions) => LibraryMetadata {
+            read_at: Utc::now(),
+            definitions,
+            error: None,
+        },
+        Err(err) => {
+            log::warn!("Failed to read library metadata for {asset_path:?}: {err}");
+            LibraryMetadata {
+                read_at: Utc::now(),
+                definitions: Vec::new(),
+                error: Some(err.to_string()),
+            }
+        }
+    }
 }
 
 /// Each daemon-managed asset has a corresponding config file that contains
@@ -118,6 +180,45 @@ fn get_root_dir() -> Result<PathBuf> {
     Ok(root_dir)
 }
 
+/// Extracts the icon and thumbnail of an asset and stores them as PNGs next
+/// to its config file. Images that no longer exist in the library are removed.
+fn write_asset_images(config_path: &Path, asset_config: &AssetConfig) -> Result<()> {
+    let definition = asset_config.definition.clone().or_else(|| {
+        let library = asset_config.library.as_ref()?;
+        Some(library.definitions.first()?.name.clone())
+    });
+
+    let images = match definition {
+        Some(definition) => read_definition_images(&asset_config.asset_path, &definition)
+            .unwrap_or_else(|err| {
+                log::warn!(
+                    "Failed to read images for {:?}: {err}",
+                    asset_config.asset_path
+                );
+                DefinitionImages::default()
+            }),
+        None => DefinitionImages::default(),
+    };
+
+    for (kind, image, size) in [
+        (AssetImageKind::Icon, &images.icon, ICON_SIZE),
+        (AssetImageKind::Thumbnail, &images.thumbnail, THUMBNAIL_SIZE),
+    ] {
+        let image_path = config_path.with_extension(kind.extension());
+
+        let png = image.as_ref().and_then(|image| render_png(image, size));
+        match png {
+            Some(png) => fs::write(&image_path, png)
+                .map_err(|err| AssetDirError::FsError(err.to_string()))?,
+            None if image_path.exists() => fs::remove_file(&image_path)
+                .map_err(|err| AssetDirError::FsError(err.to_string()))?,
+            None => {}
+        }
+    }
+
+    Ok(())
+}
+
 /// Creates a daemon-managed folder.
 pub fn create_folder(directory: &Path, display_name: &str) -> Result<String> {
     let root_dir = get_root_dir()?;
@@ -153,7 +254,12 @@ pub fn create_folder(directory: &Path, display_name: &str) -> Result<String> {
 }
 
 /// Saves an asset pointer to the daemon-managed asset directory.
-pub fn save_asset(directory: &Path, asset_path: &Path, display_name: &str) -> Result<String> {
+pub fn save_asset(
+    directory: &Path,
+    asset_path: &Path,
+    display_name: &str,
+    definition: Option<&str>,
+) -> Result<String> {
     let root_dir = get_root_dir()?;
     let dir = root_dir.join(directory);
 
@@ -174,6 +280,9 @@ pub fn save_asset(directory: &Path, asset_path: &Path, display_name: &str) -> Re
             asset_type: asset_path.into(),
             asset_path: asset_path.to_owned(),
             asset_exists: asset_path.exists(),
+            definition: definition.map(str::to_owned),
+            library: Some(read_library_metadata(asset_path)),
+            presets: Vec::new(),
         }),
     };
 
@@ -183,6 +292,10 @@ pub fn save_asset(directory: &Path, asset_path: &Path, display_name: &str) -> Re
     )
     .map_err(|err| AssetDirError::FsError(err.to_string()))?;
 
+    if let FileType::Asset(config) = &asset_config.meta {
+        write_asset_images(&asset_config_path, config)?;
+    }
+
     log::debug!("Saved asset {asset_path:?}");
 
     Ok(id)
@@ -227,6 +340,26 @@ fn list_files_recursive(current_dir: &Path) -> Result<Vec<FileConfig>> {
                 if asset_modified > file_config.date_modified {
                     file_config.date_modified = asset_modified;
                 }
+
+                // Refresh the cached library metadata if the asset file has
+                // changed since it was read. Failing to save the refresh only
+                // means it's done again next time, so it doesn't fail the
+                // listing.
+                let is_stale = asset_config
+                    .library
+                    .as_ref()
+                    .map_or(true, |library| asset_modified > library.read_at);
+                if is_stale {
+                    asset_config.library = Some(read_library_metadata(&asset_config.asset_path));
+                    if let Err(err) = write_asset_images(&path, asset_config) {
+                        log::warn!("Failed to write images for {path:?}: {err}");
+                    }
+                    if let Err(err) =
+                        fs::write(&path, serde_json::to_string_pretty(&file_config).unwrap())
+                    {
+                        log::warn!("Failed to save refreshed config {path:?}: {err}");
+                    }
+                }
             }
 
             files.push(file_config);
@@ -260,6 +393,13 @@ pub fn delete_file(path: &Path) -> Result<()> {
         fs::remove_file(&config_path).map_err(|err| AssetDirError::FsError(err.to_string()))?;
     }
 
+    for kind in AssetImageKind::ALL {
+        let image_path = file_path.with_extension(kind.extension());
+        if image_path.exists() {
+            fs::remove_file(&image_path).map_err(|err| AssetDirError::FsError(err.to_string()))?;
+        }
+    }
+
     if !deleted_something {
         return Err(AssetDirError::FileDoesNotExist(
             path.to_string_lossy().to_string(),
@@ -298,6 +438,47 @@ pub fn rename_file(path: &Path, new_name: &str) -> Result<()> {
     Ok(())
 }
 
+/// Reads the config of a daemon-managed asset.
+pub fn get_asset_config(path: &Path) -> Result<AssetConfig> {
+    let root_dir = get_root_dir()?;
+    let file_path = root_dir.join(path).with_extension("json");
+
+    if !file_path.exists() {
+        return Err(AssetDirError::FileDoesNotExist(
+            file_path.to_string_lossy().to_string(),
+        ));
+    }
+
+    let file_config: FileConfig = serde_json::from_reader(
+        fs::File::open(&file_path).map_err(|err| AssetDirError::FsError(err.to_string()))?,
+    )
+    .map_err(|err| AssetDirError::SerdeError(err.to_string()))?;
+
+    match file_config.meta {
+        FileType::Asset(asset_config) => Ok(asset_config),
+        FileType::Folder(_) => Err(AssetDirError::NotAnAsset(
+            path.to_string_lossy().to_string(),
+        )),
+    }
+}
+
+/// Registers a single definition from an asset library that is already in
+/// the daemon-managed asset directory as its own entry.
+pub fn save_asset_definition(
+    directory: &Path,
+    library: &Path,
+    definition: &str,
+    display_name: &str,
+) -> Result<String> {
+    let library_config = get_asset_config(library)?;
+    save_asset(
+        directory,
+        &library_config.asset_path,
+        display_name,
+        Some(definition),
+    )
+}
+
 pub fn list_files() -> Result<Vec<FileConfig>> {
     let root_dir = get_root_dir()?;
     log::debug!("Listing files in {root_dir:?}");
@@ -309,3 +490,152 @@ pub fn list_files() -> Result<Vec<FileConfig>> {
     let children = list_files_recursive(&root_dir)?;
     Ok(children)
 }
+
+/// Stores the preview image rendered from the latest cook of an asset.
+pub fn save_asset_preview(path: &Path, png: &[u8]) -> Result<()> {
+    // Make sure the asset still exists, so previews aren't left behind.
+    get_asset_config(path)?;
+
+    let root_dir = get_root_dir()?;
+    let image_path = root_dir
+        .join(path)
+        .with_extension(AssetImageKind::Preview.extension());
+    fs::write(&image_path, png).map_err(|err| AssetDirError::FsError(err.to_string()))?;
+
+    log::debug!("Saved preview {image_path:?}");
+    Ok(())
+}
+
+/// Returns the PNG image of a daemon-managed asset, and when it was written.
+pub fn get_asset_image(path: &Path, kind: AssetImageKind) -> Result<(Vec<u8>, DateTime<Utc>)> {
+    let root_dir = get_root_dir()?;
+    let image_path = root_dir.join(path).with_extension(kind.extension());
+
+    if !image_path.exists() {
+        return Err(AssetDirError::FileDoesNotExist(
+            image_path.to_string_lossy().to_string(),
+        ));
+    }
+
+    let modified: DateTime<Utc> = fs::metadata(&image_path)
+        .map_err(|err| AssetDirError::FsError(err.to_string()))?
+        .modified()
+        .map_err(|err| AssetDirError::FsError(err.to_string()))?
+        .into();
+    let png = fs::read(&image_path).map_err(|err| AssetDirError::FsError(err.to_string()))?;
+
+    Ok((png, modified))
+}
+
+/// Reads the config of a daemon-managed asset, lets `update` change it and
+/// writes it back.
+fn update_asset_config<T>(
+    path: &Path,
+    update: impl FnOnce(&mut AssetConfig) -> Result<T>,
+) -> Result<T> {
+    let root_dir = get_root_dir()?;
+    let file_path = root_dir.join(path).with_extension("json");
+
+    if !file_path.exists() {
+        return Err(AssetDirError::FileDoesNotExist(
+            file_path.to_string_lossy().to_string(),
+        ));
+    }
+
+    let mut file_config: FileConfig = serde_json::from_reader(
+        fs::File::open(&file_path).map_err(|err| AssetDirError::FsError(err.to_string()))?,
+    )
+    .map_err(|err| AssetDirError::SerdeError(err.to_string()))?;
+
+    let FileType::Asset(asset_config) = &mut file_config.meta else {
+        return Err(AssetDirError::NotAnAsset(
+            path.to_string_lossy().to_string(),
+        ));
+    };
+    let result = update(asset_config)?;
+
+    fs::write(
+        &file_path,
+        serde_json::to_string_pretty(&file_config).unwrap(),
+    )
+    .map_err(|err| AssetDirError::FsError(err.to_string()))?;
+
+    Ok(result)
+}
+
+fn find_preset<'a>(
+    presets: &'a mut [ParameterPreset],
+    name: &str,
+) -> Result<&'a mut ParameterPreset> {
+    presets
+        .iter_mut()
+        .find(|preset| preset.name == name)
+        .ok_or_else(|| AssetDirError::PresetNotFound(name.to_owned()))
+}
+
+pub fn list_presets(path: &Path) -> Result<Vec<ParameterPreset>> {
+    Ok(get_asset_config(path)?.presets)
+}
+
+pub fn get_preset(path: &Path, name: &str) -> Result<ParameterPreset> {
+    get_asset_config(path)?
+        .presets
+        .into_iter()
+        .find(|preset| preset.name == name)
+        .ok_or_else(|| AssetDirError::PresetNotFound(name.to_owned()))
+}
+
+/// Saves a preset, replacing the values of any preset with the same name.
+pub fn save_preset(path: &Path, name: &str, values: PresetValues) -> Result<ParameterPreset> {
+    let preset = ParameterPreset {
+        name: name.to_owned(),
+        date_modified: Utc::now(),
+        values,
+    };
+
+    update_asset_config(path, |asset_config| {
+        match find_preset(&mut asset_config.presets, name) {
+            Ok(existing) => *existing = preset.clone(),
+            Err(_) => asset_config.presets.push(preset.clone()),
+        }
+        Ok(())
+    })?;
+
+    log::debug!("Saved preset {name} for {path:?}");
+    Ok(preset)
+}
+
+pub fn rename_preset(path: &Path, name: &str, new_name: &str) -> Result<()> {
+    update_asset_config(path, |asset_config| {
+        if name != new_name
+            && asset_config
+                .presets
+                .iter()
+                .any(|preset| preset.name == new_name)
+        {
+            return Err(AssetDirError::PresetExists(new_name.to_owned()));
+        }
+
+        let preset = find_preset(&mut asset_config.presets, name)?;
+        preset.name = new_name.to_owned();
+        preset.date_modified = Utc::now();
+        Ok(())
+    })?;
+
+    log::debug!("Renamed preset {name} to {new_name} for {path:?}");
+    Ok(())
+}
+
+pub fn delete_preset(path: &Path, name: &str) -> Result<()> {
+    update_asset_config(path, |asset_config| {
+        let count = asset_config.presets.len();
+        asset_config.presets.retain(|preset| preset.name != name);
+        if asset_config.presets.len() == count {
+            return Err(AssetDirError::PresetNotFound(name.to_owned()));
+        }
+        Ok(())
+    })?;
+
+    log::debug!("Deleted preset {name} for {path:?}");
+    Ok(())
+}
@@ -0,0 +1,74 @@
+/// This module rasterizes icons and thumbnails embedded in asset libraries to
+/// small PNGs for the plugin's asset browser. Everything is rendered on the
+/// CPU, so it works on machines without a GPU.
+
+use resvg::{
+    tiny_skia::{FilterQuality, Pixmap, PixmapPaint, Transform},
+    usvg::{self, PostProcessingSteps, TreeParsing, TreePostProc},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::library_index::EmbeddedImage;
+
+/// Icons are shown next to every row of the asset list.
+pub const ICON_SIZE: u32 = 64;
+/// Thumbnails are shown when an asset is selected.
+pub const THUMBNAIL_SIZE: u32 = 256;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AssetImageKind {
+    Icon,
+    Thumbnail,
+    /// Rendered from the latest cook of the asset.
+    Preview,
+}
+
+impl AssetImageKind {
+    pub const ALL: [Self; 3] = [Self::Icon, Self::Thumbnail, Self::Preview];
+
+    /// Extension of the image file stored next to an asset's config file.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Icon => "icon.png",
+            Self::Thumbnail => "thumbnail.png",
+            Self::Preview => "preview.png",
+        }
+    }
+}
+
+/// Returns the transform that fits an image of the given size into a square,
+/// keeping its aspect ratio and centering it.
+fn fit_transform(width: f32, height: f32, size: u32) -> Transform {
+    let size = size as f32;
+    let scale = (size / width).min(size / height);
+    Transform::from_scale(scale, scale)
+        .post_translate((size - width * scale) / 2.0, (size - height * scale) / 2.0)
+}
+
+/// Renders an embedded image to a square PNG of the given size. Returns
+/// `None` if the image can't be decoded.
+pub fn render_png(image: &EmbeddedImage, size: u32) -> Option<Vec<u8>> {
+    let mut pixmap = Pixmap::new(size, size)?;
+
+    match image {
+        EmbeddedImage::Svg(data) => {
+            let mut tree = usvg::Tree::from_data(data, &usvg::Options::default()).ok()?;
+            tree.postprocess(PostProcessingSteps::default());
+
+            let transform = fit_transform(tree.size.width(), tree.size.height(), size);
+            resvg::render(&tree, transform, &mut pixmap.as_mut());
+        }
+        EmbeddedImage::Png(data) => {
+            let source = Pixmap::decode_png(data).ok()?;
+            let transform = fit_transform(source.width() as f32, source.height() as f32, size);
+            let paint = PixmapPaint {
+                quality: FilterQuality::Bicubic,
+                ..Default::default()
+            };
+            pixmap.draw_pixmap(0, 0, source.as_ref(), &paint, transform, None);
+        }
+    }
+
+    pixmap.encode_png().ok()
+}
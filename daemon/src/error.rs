@@ -4,8 +4,8 @@ use serde_json::json;
 use thiserror::Error;
 
 use crate::{
-    asset::AssetError, asset_dir::AssetDirError, routes::OpenAssetError, session::SessionError,
-    state::StateError, utils::EnvironmentError,
+    asset::AssetError, asset_dir::AssetDirError, instance_store::InstanceStoreError,
+    routes::OpenAssetError, session::SessionError, state::StateError, utils::EnvironmentError,
 };
 
 #[derive(Debug, Error, Serialize)]
@@ -18,6 +18,7 @@ pub enum AppError {
     EnvironmentError(EnvironmentError),
     StateError(StateError),
     AssetDirError(AssetDirError),
+    InstanceStoreError(InstanceStoreError),
 }
 
 /// Macro to automate the implementation of the `From` trait for variants of the
@@ -46,19 +47,50 @@ from_variant!(
     SessionError,
     EnvironmentError,
     StateError,
-    AssetDirError
+    AssetDirError,
+    InstanceStoreError
 );
 
 impl IntoResponse for AppError {
     fn into_response(self) -> axum::response::Response {
         let status = match self {
             Self::OpenAssetError(_) => StatusCode::BAD_REQUEST,
+            Self::SessionError(
+                SessionError::AssetCycle
+                | SessionError::NoAssetDirEntry(_)
+                | SessionError::NothingToUndo(_)
+                | SessionError::NothingToRedo(_)
+                | SessionError::InputNotStored { .. },
+            ) => StatusCode::BAD_REQUEST,
+            Self::SessionError(SessionError::InstanceLoaded { .. }) => StatusCode::CONFLICT,
+            Self::SessionError(
+                SessionError::SessionNotFound
+                | SessionError::AssetNotFound(_)
+                | SessionError::ConnectionNotFound { .. },
+            ) => StatusCode::NOT_FOUND,
+            Self::AssetError(ref err)
+            | Self::SessionError(
+                SessionError::AssetError(ref err) | SessionError::NewAssetError(ref err),
+            ) if err.is_not_found() => StatusCode::NOT_FOUND,
+            Self::AssetError(ref err)
+            | Self::SessionError(
+                SessionError::AssetError(ref err) | SessionError::NewAssetError(ref err),
+            ) if err.is_bad_request() => StatusCode::BAD_REQUEST,
+            Self::AssetDirError(
+                AssetDirError::FileDoesNotExist(_) | AssetDirError::PresetNotFound(_),
+            ) => StatusCode::NOT_FOUND,
+            Self::AssetDirError(AssetDirError::PresetExists(_)) => StatusCode::CONFLICT,
+            Self::InstanceStoreError(InstanceStoreError::InvalidKey(_)) => StatusCode::BAD_REQUEST,
+            Self::InstanceStoreError(InstanceStoreError::InstanceNotFound(_)) => {
+                StatusCode::NOT_FOUND
+            }
 
             Self::AssetError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Self::SessionError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Self::EnvironmentError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Self::StateError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Self::AssetDirError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::InstanceStoreError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         };
 
         let body = Json(json!({
@@ -0,0 +1,143 @@
+/// This module persists loaded asset instances, so a Model generated in one
+/// Studio session can be recooked after the place is reopened.
+///
+/// Each instance is stored as its own file in the config directory, named by
+/// a stable key that the plugin keeps on the generated Model. Inputs fed by
+/// another asset refer to that asset by its own instance key.
+
+use std::{fs, path::PathBuf};
+
+use chrono::{DateTime, Utc};
+use nanoid::nanoid;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    asset::{ConversionOptions, InputGeometry, ParameterSnapshot},
+    state::get_config_dir,
+};
+
+#[derive(Debug, Error, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstanceStoreError {
+    #[error("An unknown file system error occurred")]
+    FsError(String),
+    #[error("An instance file could not be serialized or deserialized")]
+    SerdeError(String),
+    #[error("{0} is not a valid instance key")]
+    InvalidKey(String),
+    #[error("No instance is stored with key {0}")]
+    InstanceNotFound(String),
+}
+
+type Result<T> = std::result::Result<T, InstanceStoreError>;
+
+/// Where the geometry of an input comes from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum InputSource {
+    /// Geometry sent by the plugin.
+    #[serde(rename_all = "camelCase")]
+    Geometry {
+        geometry: InputGeometry,
+        units_per_stud: f32,
+    },
+    /// An output of another stored instance.
+    #[serde(rename_all = "camelCase")]
+    Asset {
+        instance_key: String,
+        output_index: i32,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceInput {
+    pub input_index: i32,
+    #[serde(flatten)]
+    pub source: InputSource,
+}
+
+/// Everything needed to load an asset again and cook the same result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetInstance {
+    pub key: String,
+    /// Path of the asset in the daemon-managed asset directory.
+    pub asset_path: PathBuf,
+    /// The asset definition that was instantiated from the library.
+    pub definition: String,
+    pub date_modified: DateTime<Utc>,
+    /// Parameter values, and the expressions of parameters driven by them.
+    #[serde(flatten)]
+    pub parameters: ParameterSnapshot,
+    pub inputs: Vec<InstanceInput>,
+    /// Options of the latest cook.
+    pub options: ConversionOptions,
+}
+
+/// Generates a key for a new instance.
+pub fn new_key() -> String {
+    nanoid!(10)
+}
+
+fn get_instances_dir() -> Result<PathBuf> {
+    let config_dir =
+        get_config_dir().map_err(|err| InstanceStoreError::FsError(err.to_string()))?;
+    Ok(config_dir.join("Instances"))
+}
+
+fn get_instance_path(key: &str) -> Result<PathBuf> {
+    // Keys come from the client, so they must not be able to escape the
+    // instances directory.
+    let valid = !key.is_empty()
+        && key
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || char == '_' || char == '-');
+    if !valid {
+        return Err(InstanceStoreError::InvalidKey(key.to_owned()));
+    }
+
+    Ok(get_instances_dir()?.join(format!("{key}.json")))
+}
+
+/// Stores an instance, replacing any instance stored with the same key.
+pub fn save_instance(instance: &AssetInstance) -> Result<()> {
+    let instances_dir = get_instances_dir()?;
+    fs::create_dir_all(&instances_dir)
+        .map_err(|err| InstanceStoreError::FsError(err.to_string()))?;
+
+    let instance_path = get_instance_path(&instance.key)?;
+    let contents = serde_json::to_string_pretty(instance)
+        .map_err(|err| InstanceStoreError::SerdeError(err.to_string()))?;
+    fs::write(&instance_path, contents)
+        .map_err(|err| InstanceStoreError::FsError(err.to_string()))?;
+
+    log::debug!("Saved asset instance to {instance_path:?}");
+    Ok(())
+}
+
+/// Reads the instance stored with a key.
+pub fn get_instance(key: &str) -> Result<AssetInstance> {
+    let instance_path = get_instance_path(key)?;
+    if !instance_path.exists() {
+        return Err(InstanceStoreError::InstanceNotFound(key.to_owned()));
+    }
+
+    let contents = fs::read_to_string(&instance_path)
+        .map_err(|err| InstanceStoreError::FsError(err.to_string()))?;
+    serde_json::from_str(&contents).map_err(|err| InstanceStoreError::SerdeError(err.to_string()))
+}
+
+/// Deletes the instance stored with a key.
+pub fn delete_instance(key: &str) -> Result<()> {
+    let instance_path = get_instance_path(key)?;
+    if !instance_path.exists() {
+        return Err(InstanceStoreError::InstanceNotFound(key.to_owned()));
+    }
+
+    fs::remove_file(&instance_path).map_err(|err| InstanceStoreError::FsError(err.to_string()))?;
+
+    log::debug!("Deleted asset instance {instance_path:?}");
+    Ok(())
+}
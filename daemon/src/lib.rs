@@ -3,7 +3,10 @@
 pub mod application;
 pub mod asset;
 pub mod asset_dir;
+pub mod asset_images;
 pub mod error;
+pub mod instance_store;
+pub mod library_index;
 pub mod message;
 pub mod routes;
 pub mod session;
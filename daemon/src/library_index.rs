@@ -0,0 +1,675 @@
+/// This module reads the metadata of HDA libraries straight from disk, so the
+/// asset browser can show what a library contains without a Houdini session
+/// or license.
+///
+/// Packed libraries (`.hda` and `.otl` files) are a binary index of named
+/// sections, followed by the contents of those sections. All integers are
+/// big-endian, and strings are prefixed with their length:
+///
+/// ```text
+/// "INDX"
+/// u32 description length, description
+/// u32 (unused)
+/// u32 section count
+/// per section: string name, u32 offset, u32 size, u32 modification time
+/// section contents, with offsets relative to the end of the index
+/// ```
+///
+/// Every definition has a section named after it, such as `Sop/scatter`,
+/// which is itself an index of the definition's sections (`DialogScript`,
+/// `Help`, `IconSVG`, ...). The library's `INDEX_SECTION` lists the
+/// definitions:
+///
+/// ```text
+/// u32 definition count
+/// per definition:
+///   u32 format version
+///   string operator, label, path, icon, author, copyright, table
+///   string comment, license, extra info, user info
+///   u32 minimum inputs, u32 maximum inputs
+///   u32 modification time, u8 subnet, u8 python, u8 empty
+/// ```
+///
+/// Libraries expanded into a directory with `hotl -t` store the same index as
+/// plain text instead, with one block of `Key: Value` lines per definition,
+/// and every section as a separate file.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+const INDEX_MAGIC: &[u8] = b"INDX";
+const INDEX_SECTION: &str = "INDEX_SECTION";
+/// The only layout of `INDEX_SECTION` definitions this module understands.
+const DEFINITION_FORMAT_VERSION: u32 = 4;
+const INDEX_SECTION_FILE: &str = "INDEX__SECTION";
+const SECTIONS_LIST_FILE: &str = "Sections.list";
+const HELP_SECTION: &str = "Help";
+/// Extra files whose name starts with this are used as thumbnails.
+const THUMBNAIL_PREFIX: &str = "thumbnail";
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+const SVG_START: &[u8] = b"<svg";
+
+#[derive(Debug, Error, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LibraryIndexError {
+    #[error("Failed to read asset library")]
+    ReadLibrary(String),
+    #[error("The file is not an asset library")]
+    NotALibrary,
+    #[error("The asset library index is invalid: {0}")]
+    InvalidIndex(&'static str),
+    #[error("Unsupported asset library index version {0}")]
+    UnsupportedVersion(u32),
+}
+
+type Result<T> = std::result::Result<T, LibraryIndexError>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DefinitionMetadata {
+    /// Name used by HAPI to instantiate the definition, such as
+    /// `Sop/studio::scatter::1.0`.
+    pub name: String,
+    pub label: String,
+    /// Operator table (network type) the definition belongs to.
+    pub table: String,
+    pub version: Option<String>,
+    /// Either the name of a built-in Houdini icon, or an `opdef:` path to an
+    /// icon embedded in the library.
+    pub icon: Option<String>,
+    pub help: Option<String>,
+    pub min_inputs: Option<u32>,
+    pub max_inputs: Option<u32>,
+}
+
+/// Reads big-endian values from a packed library.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(LibraryIndexError::InvalidIndex("unexpected end of data"))?;
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let len = self.u32()? as usize;
+        Ok(String::from_utf8_lossy(self.take(len)?).into_owned())
+    }
+}
+
+/// A named section of a packed library or definition.
+struct Section<'a> {
+    name: String,
+    data: &'a [u8],
+}
+
+/// Reads the index at the start of a packed library or definition section.
+fn read_sections(bytes: &[u8]) -> Result<Vec<Section<'_>>> {
+    let mut reader = Reader::new(bytes);
+    if !bytes.starts_with(INDEX_MAGIC) {
+        return Err(LibraryIndexError::NotALibrary);
+    }
+    reader.take(INDEX_MAGIC.len())?;
+
+    let description_len = reader.u32()? as usize;
+    reader.take(description_len)?;
+    reader.u32()?;
+
+    let count = reader.u32()?;
+    let mut entries = Vec::new();
+    for _ in 0..count {
+        let name = reader.string()?;
+        let offset = reader.u32()? as usize;
+        let size = reader.u32()? as usize;
+        let _modified = reader.u32()?;
+        entries.push((name, offset, size));
+    }
+
+    let data = &bytes[reader.position..];
+    entries
+        .into_iter()
+        .map(|(name, offset, size)| {
+            let data = offset
+                .checked_add(size)
+                .and_then(|end| data.get(offset..end))
+                .ok_or(LibraryIndexError::InvalidIndex("section is out of bounds"))?;
+            Ok(Section { name, data })
+        })
+        .collect()
+}
+
+fn find_section<'a, 'b>(sections: &'b [Section<'a>], name: &str) -> Option<&'b Section<'a>> {
+    sections.iter().find(|section| section.name == name)
+}
+
+/// One definition listed in a library's index.
+#[derive(Debug)]
+struct IndexEntry {
+    operator: String,
+    label: String,
+    /// An `oplib:` path, ending in the name of the definition's section.
+    path: String,
+    icon: String,
+    table: String,
+    version: Option<String>,
+    min_inputs: Option<u32>,
+    max_inputs: Option<u32>,
+}
+
+impl IndexEntry {
+    fn read(reader: &mut Reader) -> Result<Self> {
+        let version = reader.u32()?;
+        if version != DEFINITION_FORMAT_VERSION {
+            return Err(LibraryIndexError::UnsupportedVersion(version));
+        }
+
+        let operator = reader.string()?;
+        let label = reader.string()?;
+        let path = reader.string()?;
+        let icon = reader.string()?;
+        let _author = reader.string()?;
+        let _copyright = reader.string()?;
+        let table = reader.string()?;
+        for _ in ["comment", "license", "extra", "user"] {
+            reader.string()?;
+        }
+        let min_inputs = reader.u32()?;
+        let max_inputs = reader.u32()?;
+        let _modified = reader.u32()?;
+        for _ in ["subnet", "python", "empty"] {
+            reader.u8()?;
+        }
+
+        Ok(Self {
+            operator,
+            label,
+            path,
+            icon,
+            table,
+            version: None,
+            min_inputs: Some(min_inputs),
+            max_inputs: Some(max_inputs),
+        })
+    }
+
+    /// Builds an entry from a block of a plain text index.
+    fn from_block(block: &HashMap<String, String>) -> Self {
+        let value = |key: &str| block.get(key).cloned().unwrap_or_default();
+        let (min_inputs, max_inputs) = block
+            .get("Inputs")
+            .map_or((None, None), |inputs| parse_inputs(inputs));
+
+        Self {
+            operator: value("Operator"),
+            label: value("Label"),
+            path: value("Path"),
+            icon: value("Icon"),
+            table: value("Table"),
+            version: Some(value("Version")).filter(|version| !version.is_empty()),
+            min_inputs,
+            max_inputs,
+        }
+    }
+
+    fn name(&self) -> Option<String> {
+        if self.operator.is_empty() || self.table.is_empty() {
+            return None;
+        }
+        Some(format!("{}/{}", self.table, self.operator))
+    }
+
+    /// Returns the name of the section holding the definition, such as
+    /// `Sop/scatter` or `studio::Sop/scatter::1.0`.
+    fn section(&self) -> Option<&str> {
+        let (_, section) = self.path.rsplit_once('?')?;
+        Some(section).filter(|section| !section.is_empty())
+    }
+
+    fn into_metadata(self, help: Option<String>) -> Option<DefinitionMetadata> {
+        let name = self.name()?;
+        let non_empty = |value: String| Some(value).filter(|value| !value.is_empty());
+
+        Some(DefinitionMetadata {
+            version: self
+                .version
+                .or_else(|| version_from_operator(&self.operator)),
+            label: non_empty(self.label).unwrap_or_else(|| self.operator.clone()),
+            icon: non_empty(self.icon),
+            table: self.table,
+            help,
+            min_inputs: self.min_inputs,
+            max_inputs: self.max_inputs,
+            name,
+        })
+    }
+}
+
+/// Reads the definitions listed in the `INDEX_SECTION` of a packed library.
+fn read_index_entries(sections: &[Section]) -> Result<Vec<IndexEntry>> {
+    let index = find_section(sections, INDEX_SECTION)
+        .ok_or(LibraryIndexError::InvalidIndex("missing INDEX_SECTION"))?;
+
+    let mut reader = Reader::new(index.data);
+    let count = reader.u32()?;
+    (0..count).map(|_| IndexEntry::read(&mut reader)).collect()
+}
+
+/// Returns the sections of the definition listed by an index entry.
+fn definition_sections<'a>(
+    sections: &[Section<'a>],
+    entry: &IndexEntry,
+) -> Result<Option<Vec<Section<'a>>>> {
+    let Some(section) = entry
+        .section()
+        .and_then(|name| find_section(sections, name))
+    else {
+        return Ok(None);
+    };
+    read_sections(section.data).map(Some)
+}
+
+fn read_help(data: &[u8]) -> Option<String> {
+    let help = String::from_utf8_lossy(data).trim().to_owned();
+    Some(help).filter(|help| !help.is_empty())
+}
+
+/// Returns the metadata of every definition in a packed library.
+fn read_packed_library(bytes: &[u8]) -> Result<Vec<DefinitionMetadata>> {
+    let sections = read_sections(bytes)?;
+
+    let mut definitions: Vec<DefinitionMetadata> = Vec::new();
+    for entry in read_index_entries(&sections)? {
+        let help = definition_sections(&sections, &entry)?.and_then(|sections| {
+            find_section(&sections, HELP_SECTION).and_then(|help| read_help(help.data))
+        });
+
+        if let Some(definition) = entry.into_metadata(help) {
+            if definitions
+                .iter()
+                .all(|other| other.name != definition.name)
+            {
+                definitions.push(definition);
+            }
+        }
+    }
+
+    Ok(definitions)
+}
+
+/// Returns the metadata of every definition in a library.
+pub fn read_library_index(path: &Path) -> Result<Vec<DefinitionMetadata>> {
+    if path.is_dir() {
+        return read_expanded_library(path);
+    }
+
+    let bytes = fs::read(path).map_err(|err| LibraryIndexError::ReadLibrary(err.to_string()))?;
+    read_packed_library(&bytes)
+}
+
+/// Maps the name of every definition in an expanded library to the directory
+/// its sections were expanded into, using `Sections.list`. Each line looks
+/// like `Sop_1scatter Sop/scatter`.
+fn expanded_section_dirs(dir: &Path) -> HashMap<String, PathBuf> {
+    fs::read_to_string(dir.join(SECTIONS_LIST_FILE))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let (file, section) = line.split_once(char::is_whitespace)?;
+            Some((section.trim().to_owned(), dir.join(file)))
+        })
+        .collect()
+}
+
+fn read_expanded_entries(dir: &Path) -> Result<Vec<IndexEntry>> {
+    let index = fs::read_to_string(dir.join(INDEX_SECTION_FILE))
+        .map_err(|_| LibraryIndexError::NotALibrary)?;
+
+    Ok(parse_index_blocks(&index)
+        .iter()
+        .map(IndexEntry::from_block)
+        .collect())
+}
+
+fn read_expanded_library(dir: &Path) -> Result<Vec<DefinitionMetadata>> {
+    let section_dirs = expanded_section_dirs(dir);
+
+    Ok(read_expanded_entries(dir)?
+        .into_iter()
+        .filter_map(|entry| {
+            let help = entry
+                .section()
+                .and_then(|section| section_dirs.get(section))
+                .and_then(|section_dir| fs::read(section_dir.join(HELP_SECTION)).ok())
+                .and_then(|help| read_help(&help));
+            entry.into_metadata(help)
+        })
+        .collect())
+}
+
+/// Splits plain text index into blocks of `Key: Value` pairs, one per
+/// definition. Duplicate definitions are only returned once.
+fn parse_index_blocks(text: &str) -> Vec<HashMap<String, String>> {
+    let mut blocks: Vec<HashMap<String, String>> = Vec::new();
+    let mut current: Option<HashMap<String, String>> = None;
+
+    for line in text.lines() {
+        let entry = line.split_once(':').and_then(|(key, value)| {
+            let valid_key = !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric());
+            valid_key.then(|| (key, value.trim().to_owned()))
+        });
+
+        match (entry, &mut current) {
+            (Some(("Operator", value)), _) => {
+                blocks.extend(current.take());
+                current = Some(HashMap::from([("Operator".to_owned(), value)]));
+            }
+            (Some((key, value)), Some(block)) => {
+                block.entry(key.to_owned()).or_insert(value);
+            }
+            (None, Some(_)) => blocks.extend(current.take()),
+            _ => {}
+        }
+    }
+    blocks.extend(current);
+
+    let mut seen = Vec::new();
+    blocks.retain(|block| match IndexEntry::from_block(block).name() {
+        Some(name) if !seen.contains(&name) => {
+            seen.push(name);
+            true
+        }
+        _ => false,
+    });
+
+    blocks
+}
+
+/// Reads the version from a namespaced operator name such as
+/// `studio::scatter::1.0`.
+fn version_from_operator(operator: &str) -> Option<String> {
+    let (_, version) = operator.rsplit_once("::")?;
+
+    let is_version = !version.is_empty()
+        && version.chars().all(|c| c.is_ascii_digit() || c == '.')
+        && version.chars().next().map_or(false, |c| c.is_ascii_digit());
+    is_version.then(|| version.to_owned())
+}
+
+/// Parses an `Inputs` value such as `1 to 4`.
+fn parse_inputs(inputs: &str) -> (Option<u32>, Option<u32>) {
+    let mut bounds = inputs.split("to").map(|bound| bound.trim().parse().ok());
+    (bounds.next().flatten(), bounds.next().flatten())
+}
+
+/// An image stored in a library section.
+#[derive(Debug)]
+pub enum EmbeddedImage {
+    Svg(Vec<u8>),
+    Png(Vec<u8>),
+}
+
+impl EmbeddedImage {
+    /// Returns the image in a section, or `None` for formats that can't be
+    /// rendered, such as Houdini's own `.pic` images.
+    fn from_bytes(bytes: Vec<u8>) -> Option<Self> {
+        if bytes.starts_with(PNG_SIGNATURE) {
+            Some(Self::Png(bytes))
+        } else if bytes
+            .windows(SVG_START.len())
+            .any(|window| window == SVG_START)
+        {
+            Some(Self::Svg(bytes))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DefinitionImages {
+    pub icon: Option<EmbeddedImage>,
+    pub thumbnail: Option<EmbeddedImage>,
+}
+
+/// Returns the definition section and section name of an icon embedded in
+/// the library, such as `Sop/scatter` and `IconSVG` for
+/// `opdef:/Sop/scatter?IconSVG`. Built-in Houdini icons return `None`.
+fn icon_section(entry: &IndexEntry) -> Option<(&str, &str)> {
+    entry.icon.strip_prefix("opdef:/")?.rsplit_once('?')
+}
+
+fn is_thumbnail(section: &str) -> bool {
+    section.to_lowercase().starts_with(THUMBNAIL_PREFIX)
+}
+
+fn read_expanded_images(dir: &Path, name: &str) -> Result<DefinitionImages> {
+    let entries = read_expanded_entries(dir)?;
+    let Some(entry) = entries
+        .iter()
+        .find(|entry| entry.name().as_deref() == Some(name))
+    else {
+        return Ok(DefinitionImages::default());
+    };
+
+    let section_dirs = expanded_section_dirs(dir);
+    let icon = icon_section(entry)
+        .and_then(|(definition, section)| {
+            let section_dir = section_dirs.get(definition)?;
+            fs::read(section_dir.join(section)).ok()
+        })
+        .and_then(EmbeddedImage::from_bytes);
+
+    let thumbnail = match entry
+        .section()
+        .and_then(|section| section_dirs.get(section))
+    {
+        Some(section_dir) => fs::read_dir(section_dir)
+            .map_err(|err| LibraryIndexError::ReadLibrary(err.to_string()))?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| is_thumbnail(&entry.file_name().to_string_lossy()))
+            .filter_map(|entry| fs::read(entry.path()).ok())
+            .find_map(EmbeddedImage::from_bytes),
+        None => None,
+    };
+
+    Ok(DefinitionImages { icon, thumbnail })
+}
+
+fn read_packed_images(bytes: &[u8], name: &str) -> Result<DefinitionImages> {
+    let sections = read_sections(bytes)?;
+    let entries = read_index_entries(&sections)?;
+    let Some(entry) = entries
+        .iter()
+        .find(|entry| entry.name().as_deref() == Some(name))
+    else {
+        return Ok(DefinitionImages::default());
+    };
+
+    // The icon can live in the sections of another definition in the
+    // library, so it's looked up by the definition its path names.
+    let mut icon = None;
+    if let Some((definition, section)) = icon_section(entry) {
+        if let Some(definition_section) = find_section(&sections, definition) {
+            let icon_sections = read_sections(definition_section.data)?;
+            icon = find_section(&icon_sections, section)
+                .and_then(|section| EmbeddedImage::from_bytes(section.data.to_vec()));
+        }
+    }
+
+    let thumbnail = definition_sections(&sections, entry)?.and_then(|sections| {
+        sections
+            .iter()
+            .filter(|section| is_thumbnail(&section.name))
+            .find_map(|section| EmbeddedImage::from_bytes(section.data.to_vec()))
+    });
+
+    Ok(DefinitionImages { icon, thumbnail })
+}
+
+/// Returns the icon and thumbnail embedded in a library for a definition.
+pub fn read_definition_images(path: &Path, name: &str) -> Result<DefinitionImages> {
+    if path.is_dir() {
+        return read_expanded_images(path, name);
+    }
+
+    let bytes = fs::read(path).map_err(|err| LibraryIndexError::ReadLibrary(err.to_string()))?;
+    read_packed_images(&bytes, name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_fixture(name: &str) -> Vec<u8> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("otls")
+            .join(name);
+        fs::read(&path).unwrap_or_else(|err| panic!("failed to read {path:?}: {err}"))
+    }
+
+    #[test]
+    fn reads_bundled_libraries() {
+        let expected = [
+            (
+                "EverGreen.otl",
+                "Sop/SideFX::EvergreenGenerator",
+                "EvergreenGenerator",
+            ),
+            ("hapi_geo.hda", "Object/hapi_geo", "Hapi Geometry Test"),
+            ("hapi_parms.hda", "Object/hapi_parms", "Hapi parms"),
+            ("hapi_script.hda", "Object/hapi_script", "Run Script"),
+            ("hapi_vol.hda", "Object/hapi_vol", "Hapi Vol"),
+            ("pdg_examples.hda", "Object/pdg_examples", "Pdg Examples"),
+            ("sesi/FourShapes.hda", "Object/FourShapes", "FourShapes"),
+            (
+                "sesi/MultiParameter_UI_Test.hda",
+                "Sop/MultiParameter_UI_Test",
+                "MultiParameter UI Test",
+            ),
+            (
+                "sesi/PackedPrimitive.hda",
+                "Object/PackedPrimitive",
+                "PackedPrimitive",
+            ),
+            ("sesi/nurbs_curve.hda", "Object/brandona_nurbs", "NURBS"),
+        ];
+
+        for (file, name, label) in expected {
+            let definitions = read_packed_library(&read_fixture(file))
+                .unwrap_or_else(|err| panic!("failed to read {file}: {err}"));
+            assert_eq!(definitions.len(), 1, "{file}");
+            assert_eq!(definitions[0].name, name, "{file}");
+            assert_eq!(definitions[0].label, label, "{file}");
+        }
+    }
+
+    #[test]
+    fn reads_definition_fields() {
+        let definitions =
+            read_packed_library(&read_fixture("sesi/MultiParameter_UI_Test.hda")).unwrap();
+        let definition = &definitions[0];
+        assert_eq!(definition.table, "Sop");
+        assert_eq!(definition.icon.as_deref(), Some("SOP_subnet"));
+        assert_eq!(definition.min_inputs, Some(0));
+        assert_eq!(definition.max_inputs, Some(1));
+        assert_eq!(definition.version, None);
+
+        let definitions = read_packed_library(&read_fixture("hapi_script.hda")).unwrap();
+        assert_eq!(
+            definitions[0].icon.as_deref(),
+            Some("opdef:/Object/hapi_script?IconSVG")
+        );
+    }
+
+    #[test]
+    fn reads_embedded_icons() {
+        let images =
+            read_packed_images(&read_fixture("hapi_script.hda"), "Object/hapi_script").unwrap();
+        assert!(matches!(images.icon, Some(EmbeddedImage::Svg(_))));
+        assert!(images.thumbnail.is_none());
+
+        // Built-in icons aren't embedded in the library.
+        let images =
+            read_packed_images(&read_fixture("hapi_parms.hda"), "Object/hapi_parms").unwrap();
+        assert!(images.icon.is_none());
+
+        // `.pic` icons are found, but can't be rendered.
+        let images = read_packed_images(
+            &read_fixture("EverGreen.otl"),
+            "Sop/SideFX::EvergreenGenerator",
+        )
+        .unwrap();
+        assert!(images.icon.is_none());
+    }
+
+    #[test]
+    fn rejects_invalid_libraries() {
+        assert!(matches!(
+            read_packed_library(b"not a library"),
+            Err(LibraryIndexError::NotALibrary)
+        ));
+
+        let bytes = read_fixture("hapi_parms.hda");
+        assert!(matches!(
+            read_packed_library(&bytes[..200]),
+            Err(LibraryIndexError::InvalidIndex(_))
+        ));
+    }
+
+    #[test]
+    fn parses_text_index() {
+        let index = "Operator:     studio::scatter::1.0\n\
+                     Label:        Scatter\n\
+                     Path:         oplib:/Sop/studio::scatter::1.0?Sop/studio::scatter::1.0\n\
+                     Icon:         SOP_scatter\n\
+                     Table:        Sop\n\
+                     Inputs:       1 to 4\n";
+
+        let entries: Vec<IndexEntry> = parse_index_blocks(index)
+            .iter()
+            .map(IndexEntry::from_block)
+            .collect();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].section(), Some("Sop/studio::scatter::1.0"));
+
+        let definition = entries
+            .into_iter()
+            .next()
+            .unwrap()
+            .into_metadata(None)
+            .unwrap();
+        assert_eq!(definition.name, "Sop/studio::scatter::1.0");
+        assert_eq!(definition.version.as_deref(), Some("1.0"));
+        assert_eq!(definition.min_inputs, Some(1));
+        assert_eq!(definition.max_inputs, Some(4));
+    }
+}
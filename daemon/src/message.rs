@@ -12,6 +12,8 @@ pub struct OpenFileSelectorOptions {
     pub name: &'static str,
     pub filters: Vec<(&'static str, &'static [&'static str])>,
     pub directory: PathBuf,
+    /// Whether to pick a folder rather than a file.
+    pub pick_folder: bool,
 }
 
 pub type AppMsgTransmitter = tokio::sync::mpsc::Sender<ApplicationMessage>;
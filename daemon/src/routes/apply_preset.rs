@@ -0,0 +1,43 @@
+use axum::{http::StatusCode, Extension, Json};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    asset::AppliedPreset,
+    asset_dir::get_preset,
+    session::{AMSessionRegistry, SessionError},
+    utils::ExtractSessionId,
+};
+
+use super::AppResponse;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplyPresetRequest {
+    pub asset_id: Uuid,
+    pub name: String,
+}
+
+pub async fn apply_preset(
+    Extension(registry): Extension<AMSessionRegistry>,
+    ExtractSessionId(session_id): ExtractSessionId,
+    Json(body): Json<ApplyPresetRequest>,
+) -> AppResponse<AppliedPreset> {
+    let mut registry = registry.lock().await;
+    let session = registry
+        .get_session_mut(&session_id)
+        .ok_or(SessionError::SessionNotFound)?;
+
+    let path = session
+        .get_asset_dir_path(body.asset_id)
+        .ok_or(SessionError::NoAssetDirEntry(body.asset_id))?;
+
+    let preset = get_preset(path, &body.name)?;
+    let label = format!("Apply preset {}", preset.name);
+    let names: Vec<&str> = preset.values.keys().map(String::as_str).collect();
+    let applied = session.change_parameters(body.asset_id, &label, Some(&names), |asset| {
+        asset.apply_preset(&preset.values)
+    })?;
+
+    Ok((StatusCode::OK, Json(applied)))
+}
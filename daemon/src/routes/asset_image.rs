@@ -0,0 +1,62 @@
+use std::path::PathBuf;
+
+use axum::{
+    extract::Query,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+
+use crate::{asset_dir::get_asset_image, asset_images::AssetImageKind, error::AppError};
+
+/// Returns how long clients may reuse an image without checking it again.
+fn cache_control(kind: AssetImageKind) -> &'static str {
+    match kind {
+        // Icons and thumbnails only change when the asset library does, which
+        // the plugin notices through `list_files`.
+        AssetImageKind::Icon | AssetImageKind::Thumbnail => "private, max-age=3600",
+        // Previews change with every cook, so they are always revalidated.
+        AssetImageKind::Preview => "private, no-cache",
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetImageQuery {
+    /// Path of the asset in the daemon-managed asset directory.
+    pub path: PathBuf,
+    pub kind: AssetImageKind,
+}
+
+pub async fn asset_image(
+    headers: HeaderMap,
+    Query(query): Query<AssetImageQuery>,
+) -> Result<Response, AppError> {
+    let (png, modified) = get_asset_image(&query.path, query.kind)?;
+    let etag = format!("\"{}\"", modified.timestamp_millis());
+
+    let cache_headers = [
+        (header::CACHE_CONTROL, cache_control(query.kind).to_owned()),
+        (header::ETAG, etag.clone()),
+        (
+            header::LAST_MODIFIED,
+            modified.format("%a, %d %b %Y %H:%M:%S GMT").to_string(),
+        ),
+    ];
+
+    let is_cached = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .map_or(false, |value| value == etag);
+    if is_cached {
+        return Ok((StatusCode::NOT_MODIFIED, cache_headers).into_response());
+    }
+
+    Ok((
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "image/png".to_owned())],
+        cache_headers,
+        png,
+    )
+        .into_response())
+}
@@ -0,0 +1,40 @@
+use axum::{http::StatusCode, Extension, Json};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    session::{AMSessionRegistry, AssetConnection, SessionError},
+    utils::ExtractSessionId,
+};
+
+use super::AppResponse;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectAssetsRequest {
+    pub source_asset_id: Uuid,
+    #[serde(default)]
+    pub output_index: i32,
+    pub target_asset_id: Uuid,
+    pub input_index: i32,
+}
+
+pub async fn connect_assets(
+    Extension(registry): Extension<AMSessionRegistry>,
+    ExtractSessionId(session_id): ExtractSessionId,
+    Json(body): Json<ConnectAssetsRequest>,
+) -> AppResponse<()> {
+    let mut registry = registry.lock().await;
+    let session = registry
+        .get_session_mut(&session_id)
+        .ok_or(SessionError::SessionNotFound)?;
+
+    session.connect_assets(AssetConnection {
+        source_asset_id: body.source_asset_id,
+        output_index: body.output_index,
+        target_asset_id: body.target_asset_id,
+        input_index: body.input_index,
+    })?;
+
+    Ok((StatusCode::OK, Json(())))
+}
@@ -0,0 +1,48 @@
+use axum::{http::StatusCode, Extension, Json};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    asset::{ConversionOptions, CookResult},
+    asset_dir::save_asset_preview,
+    session::{AMSessionRegistry, SessionError},
+    utils::ExtractSessionId,
+};
+
+use super::AppResponse;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CookAssetRequest {
+    pub asset_id: Uuid,
+    #[serde(default)]
+    pub options: ConversionOptions,
+}
+
+pub async fn cook_asset(
+    Extension(registry): Extension<AMSessionRegistry>,
+    ExtractSessionId(session_id): ExtractSessionId,
+    Json(body): Json<CookAssetRequest>,
+) -> AppResponse<CookResult> {
+    let mut registry = registry.lock().await;
+    let session = registry
+        .get_session_mut(&session_id)
+        .ok_or(SessionError::SessionNotFound)?;
+
+    let mut cook_result = session.cook_asset(body.asset_id, &body.options)?;
+
+    if let Some(preview) = cook_result.take_preview() {
+        match session.get_asset_dir_path(body.asset_id) {
+            Some(path) => {
+                // The cook itself succeeded, so a preview that can't be saved
+                // shouldn't discard its result.
+                if let Err(err) = save_asset_preview(path, &preview) {
+                    log::warn!("Failed to save preview for {path:?}: {err}");
+                }
+            }
+            None => log::warn!("Asset {} has no asset directory entry", body.asset_id),
+        }
+    }
+
+    Ok((StatusCode::OK, Json(cook_result)))
+}
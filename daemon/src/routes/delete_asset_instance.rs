@@ -0,0 +1,19 @@
+use axum::{http::StatusCode, Json};
+use serde::Deserialize;
+
+use crate::instance_store::delete_instance;
+
+use super::AppResponse;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteAssetInstanceRequest {
+    pub key: String,
+}
+
+pub async fn delete_asset_instance(
+    Json(body): Json<DeleteAssetInstanceRequest>,
+) -> AppResponse<()> {
+    delete_instance(&body.key)?;
+    Ok((StatusCode::OK, Json(())))
+}
@@ -0,0 +1,21 @@
+use std::path::PathBuf;
+
+use axum::{http::StatusCode, Json};
+use serde::Deserialize;
+
+use crate::error::AppError;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeletePresetRequest {
+    /// Path of the asset in the daemon-managed asset directory.
+    pub path: PathBuf,
+    pub name: String,
+}
+
+pub async fn delete_preset(
+    Json(body): Json<DeletePresetRequest>,
+) -> Result<(StatusCode, Json<()>), AppError> {
+    crate::asset_dir::delete_preset(&body.path, &body.name)?;
+    Ok((StatusCode::OK, Json(())))
+}
@@ -0,0 +1,32 @@
+use axum::{http::StatusCode, Extension, Json};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    session::{AMSessionRegistry, SessionError},
+    utils::ExtractSessionId,
+};
+
+use super::AppResponse;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisconnectAssetsRequest {
+    pub target_asset_id: Uuid,
+    pub input_index: i32,
+}
+
+pub async fn disconnect_assets(
+    Extension(registry): Extension<AMSessionRegistry>,
+    ExtractSessionId(session_id): ExtractSessionId,
+    Json(body): Json<DisconnectAssetsRequest>,
+) -> AppResponse<()> {
+    let mut registry = registry.lock().await;
+    let session = registry
+        .get_session_mut(&session_id)
+        .ok_or(SessionError::SessionNotFound)?;
+
+    session.disconnect_assets(body.target_asset_id, body.input_index)?;
+
+    Ok((StatusCode::OK, Json(())))
+}
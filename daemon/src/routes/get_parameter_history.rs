@@ -0,0 +1,30 @@
+use axum::{http::StatusCode, Extension, Json};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    session::{AMSessionRegistry, HistoryInfo, SessionError},
+    utils::ExtractSessionId,
+};
+
+use super::AppResponse;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetParameterHistoryRequest {
+    pub asset_id: Uuid,
+}
+
+pub async fn get_parameter_history(
+    Extension(registry): Extension<AMSessionRegistry>,
+    ExtractSessionId(session_id): ExtractSessionId,
+    Json(body): Json<GetParameterHistoryRequest>,
+) -> AppResponse<HistoryInfo> {
+    let registry = registry.lock().await;
+    let session = registry
+        .get_session(&session_id)
+        .ok_or(SessionError::SessionNotFound)?;
+
+    let history = session.parameter_history(body.asset_id)?;
+    Ok((StatusCode::OK, Json(history)))
+}
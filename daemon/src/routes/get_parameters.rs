@@ -0,0 +1,44 @@
+use axum::{http::StatusCode, Extension, Json};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    asset::AssetParameters,
+    session::{AMSessionRegistry, SessionError},
+    utils::ExtractSessionId,
+};
+
+use super::AppResponse;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetParametersRequest {
+    pub asset_id: Uuid,
+    /// Whether to return parameters arranged into their folder layout.
+    #[serde(default)]
+    pub tree: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetParametersResponse {
+    pub parameters: AssetParameters,
+}
+
+pub async fn get_parameters(
+    Extension(registry): Extension<AMSessionRegistry>,
+    ExtractSessionId(session_id): ExtractSessionId,
+    Json(body): Json<GetParametersRequest>,
+) -> AppResponse<GetParametersResponse> {
+    let registry = registry.lock().await;
+    let session = registry
+        .get_session(&session_id)
+        .ok_or(SessionError::SessionNotFound)?;
+
+    let asset = session
+        .get_asset(body.asset_id)
+        .ok_or(SessionError::AssetNotFound(body.asset_id))?;
+    let parameters = asset.get_parameters(body.tree)?;
+
+    Ok((StatusCode::OK, Json(GetParametersResponse { parameters })))
+}
@@ -0,0 +1,32 @@
+use axum::{http::StatusCode, Extension, Json};
+use serde::Serialize;
+
+use crate::{
+    session::{AMSessionRegistry, AssetConnection, SessionError},
+    utils::ExtractSessionId,
+};
+
+use super::AppResponse;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListAssetConnectionsResponse {
+    pub connections: Vec<AssetConnection>,
+}
+
+pub async fn list_asset_connections(
+    Extension(registry): Extension<AMSessionRegistry>,
+    ExtractSessionId(session_id): ExtractSessionId,
+) -> AppResponse<ListAssetConnectionsResponse> {
+    let registry = registry.lock().await;
+    let session = registry
+        .get_session(&session_id)
+        .ok_or(SessionError::SessionNotFound)?;
+
+    let connections = session.asset_connections().to_vec();
+
+    Ok((
+        StatusCode::OK,
+        Json(ListAssetConnectionsResponse { connections }),
+    ))
+}
@@ -0,0 +1,45 @@
+use std::path::PathBuf;
+
+use axum::{http::StatusCode, Extension, Json};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset::AssetDefinition,
+    asset_dir::get_asset_config,
+    session::{AMSessionRegistry, SessionError},
+    utils::ExtractSessionId,
+};
+
+use super::AppResponse;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListAssetDefinitionsRequest {
+    /// Path of the asset library in the daemon-managed asset directory.
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListAssetDefinitionsResponse {
+    pub definitions: Vec<AssetDefinition>,
+}
+
+pub async fn list_asset_definitions(
+    Extension(registry): Extension<AMSessionRegistry>,
+    ExtractSessionId(session_id): ExtractSessionId,
+    Json(body): Json<ListAssetDefinitionsRequest>,
+) -> AppResponse<ListAssetDefinitionsResponse> {
+    let registry = registry.lock().await;
+    let session = registry
+        .get_session(&session_id)
+        .ok_or(SessionError::SessionNotFound)?;
+
+    let asset_config = get_asset_config(&body.path)?;
+    let definitions = session.list_asset_definitions(&asset_config.asset_path)?;
+
+    Ok((
+        StatusCode::OK,
+        Json(ListAssetDefinitionsResponse { definitions }),
+    ))
+}
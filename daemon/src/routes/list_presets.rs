@@ -0,0 +1,26 @@
+use std::path::PathBuf;
+
+use axum::{http::StatusCode, Json};
+use serde::{Deserialize, Serialize};
+
+use crate::{asset_dir::ParameterPreset, error::AppError};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListPresetsRequest {
+    /// Path of the asset in the daemon-managed asset directory.
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListPresetsResponse {
+    pub presets: Vec<ParameterPreset>,
+}
+
+pub async fn list_presets(
+    Json(body): Json<ListPresetsRequest>,
+) -> Result<(StatusCode, Json<ListPresetsResponse>), AppError> {
+    let presets = crate::asset_dir::list_presets(&body.path)?;
+    Ok((StatusCode::OK, Json(ListPresetsResponse { presets })))
+}
@@ -0,0 +1,72 @@
+use std::path::PathBuf;
+
+use axum::{http::StatusCode, Extension, Json};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    asset::{AssetIoSpec, AssetParameters, SerializableAssetInfo},
+    asset_dir::get_asset_config,
+    session::{AMSessionRegistry, SessionError},
+    utils::ExtractSessionId,
+};
+
+use super::AppResponse;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadAssetRequest {
+    /// Path of the asset in the daemon-managed asset directory.
+    pub path: PathBuf,
+    /// The asset definition to instantiate from the library. Overrides the
+    /// definition registered in the asset directory.
+    #[serde(default)]
+    pub definition: Option<String>,
+    /// Whether to return parameters arranged into their folder layout.
+    #[serde(default)]
+    pub parameter_tree: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadAssetResponse {
+    pub id: Uuid,
+    pub asset_info: SerializableAssetInfo,
+    pub io_spec: AssetIoSpec,
+    pub parameters: AssetParameters,
+}
+
+pub async fn load_asset(
+    Extension(registry): Extension<AMSessionRegistry>,
+    ExtractSessionId(session_id): ExtractSessionId,
+    Json(body): Json<LoadAssetRequest>,
+) -> AppResponse<LoadAssetResponse> {
+    let mut registry = registry.lock().await;
+    let session = registry
+        .get_session_mut(&session_id)
+        .ok_or(SessionError::SessionNotFound)?;
+
+    let asset_config = get_asset_config(&body.path)?;
+    let definition = body.definition.or(asset_config.definition);
+    let id = session.load_asset_file(&asset_config.asset_path, definition.as_deref())?;
+    session.set_asset_dir_path(id, body.path.clone());
+
+    let asset = session
+        .get_asset(id)
+        .ok_or(SessionError::AssetNotFound(id))?;
+    let asset_info = asset.get_asset_info()?;
+    let io_spec = asset.get_io_spec()?;
+    let parameters = asset.get_parameters(body.parameter_tree)?;
+
+    log::debug!("Loaded asset {:?} with ID {id}", asset_config.asset_path);
+
+    Ok((
+        StatusCode::CREATED,
+        Json(LoadAssetResponse {
+            id,
+            asset_info,
+            io_spec,
+            parameters,
+        }),
+    ))
+}
@@ -1,18 +1,74 @@
+mod apply_preset;
+mod asset_image;
 mod close;
 mod connect;
+mod connect_assets;
+mod cook_asset;
 mod create_folder;
+mod delete_asset_instance;
 mod delete_file;
+mod delete_preset;
+mod disconnect_assets;
+mod get_parameter_history;
+mod get_parameters;
+mod insert_multiparm_instance;
+mod list_asset_connections;
+mod list_asset_definitions;
 mod list_files;
+mod list_presets;
+mod load_asset;
 mod open_asset;
+mod pick_parameter_path;
+mod press_button;
+mod redo_parameters;
+mod register_asset_definition;
+mod remove_multiparm_instance;
 mod rename_file;
+mod rename_preset;
+mod restore_asset_instance;
+mod save_asset_instance;
+mod save_preset;
+mod set_asset_input;
+mod set_multiparm_count;
+mod set_parameter_expression;
+mod set_parameters;
+mod undo_parameters;
 
+pub use apply_preset::apply_preset;
+pub use asset_image::asset_image;
 pub use close::close;
 pub use connect::connect;
+pub use connect_assets::connect_assets;
+pub use cook_asset::cook_asset;
 pub use create_folder::create_folder;
+pub use delete_asset_instance::delete_asset_instance;
 pub use delete_file::delete_file;
+pub use delete_preset::delete_preset;
+pub use disconnect_assets::disconnect_assets;
+pub use get_parameter_history::get_parameter_history;
+pub use get_parameters::get_parameters;
+pub use insert_multiparm_instance::insert_multiparm_instance;
+pub use list_asset_connections::list_asset_connections;
+pub use list_asset_definitions::list_asset_definitions;
 pub use list_files::list_files;
+pub use list_presets::list_presets;
+pub use load_asset::load_asset;
 pub use open_asset::{open_asset, OpenAssetError};
+pub use pick_parameter_path::pick_parameter_path;
+pub use press_button::press_button;
+pub use redo_parameters::redo_parameters;
+pub use register_asset_definition::register_asset_definition;
+pub use remove_multiparm_instance::remove_multiparm_instance;
 pub use rename_file::rename_file;
+pub use rename_preset::rename_preset;
+pub use restore_asset_instance::restore_asset_instance;
+pub use save_asset_instance::save_asset_instance;
+pub use save_preset::save_preset;
+pub use set_asset_input::set_asset_input;
+pub use set_multiparm_count::set_multiparm_count;
+pub use set_parameter_expression::set_parameter_expression;
+pub use set_parameters::set_parameters;
+pub use undo_parameters::undo_parameters;
 
 use axum::{http::StatusCode, Json};
 
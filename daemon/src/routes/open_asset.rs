@@ -60,6 +60,7 @@ pub async fn open_asset(
             name: "Open HDA",
             filters: vec![("HDAs", &["otl", "hda", "hdanc", "hdalc", "hdal", "hdat"])],
             directory: last_directory.to_owned(),
+            pick_folder: false,
         };
 
         tx_in
@@ -76,7 +77,7 @@ pub async fn open_asset(
             }
 
             let display_name = file.file_name().unwrap().to_string_lossy().to_string();
-            let id = save_asset(&body.directory, &file, &display_name)?;
+            let id = save_asset(&body.directory, &file, &display_name, None)?;
 
             Ok((StatusCode::OK, Json(OpenAssetResponse { id })))
         } else {
@@ -0,0 +1,101 @@
+use std::path::PathBuf;
+
+use axum::{http::StatusCode, Extension, Json};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    asset::{ParameterState, PathBase, WriteOptions},
+    message::{AppMsgReceiver, AppMsgTransmitter, ApplicationMessage, OpenFileSelectorOptions},
+    session::{AMSessionRegistry, SessionError},
+    utils::ExtractSessionId,
+};
+
+use super::AppResponse;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PickParameterPathRequest {
+    pub asset_id: Uuid,
+    /// Name of the file path parameter.
+    pub name: String,
+    /// Where the chosen path is stored relative to.
+    #[serde(default)]
+    pub base: PathBase,
+    #[serde(flatten)]
+    pub options: WriteOptions,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PickParameterPathResponse {
+    /// The value written to the parameter. Not set if the picker was closed
+    /// without choosing a path.
+    pub value: Option<String>,
+    /// Effective visibility and enabled state of the parameter, and of any
+    /// parameter whose state changed because of the write.
+    pub states: Vec<ParameterState>,
+}
+
+pub async fn pick_parameter_path(
+    Extension(tx_in): Extension<AppMsgTransmitter>,
+    Extension(rx_out): Extension<AppMsgReceiver>,
+    Extension(registry): Extension<AMSessionRegistry>,
+    ExtractSessionId(session_id): ExtractSessionId,
+    Json(body): Json<PickParameterPathRequest>,
+) -> AppResponse<PickParameterPathResponse> {
+    let mut registry = registry.lock().await;
+    let mut rx_out = rx_out.lock().await;
+
+    let session = registry
+        .get_session_mut(&session_id)
+        .ok_or(SessionError::SessionNotFound)?;
+    let asset = session
+        .get_asset(body.asset_id)
+        .ok_or(SessionError::AssetNotFound(body.asset_id))?;
+
+    let kind = asset.path_parameter_kind(&body.name)?;
+
+    // Start in the base directory, since that is where relative paths point.
+    let directory = asset
+        .path_base_directory(body.base)?
+        .or_else(|| session.state.last_opened_directory().cloned())
+        .unwrap_or_else(|| PathBuf::from("/"));
+
+    let options = OpenFileSelectorOptions {
+        name: kind.title(),
+        filters: kind.filters(),
+        directory,
+        pick_folder: kind.picks_folder(),
+    };
+
+    tx_in
+        .send(ApplicationMessage::OpenFileSelector(options))
+        .await
+        .unwrap();
+
+    let ApplicationMessage::FileSelected(Some(path)) = rx_out.recv().await.unwrap() else {
+        return Ok((
+            StatusCode::OK,
+            Json(PickParameterPathResponse {
+                value: None,
+                states: Vec::new(),
+            }),
+        ));
+    };
+
+    let (value, states) = session.change_parameters(
+        body.asset_id,
+        "Pick path",
+        Some(&[body.name.as_str()]),
+        |asset| asset.set_path_parameter(&body.name, &path, body.base, &body.options),
+    )?;
+
+    Ok((
+        StatusCode::OK,
+        Json(PickParameterPathResponse {
+            value: Some(value),
+            states,
+        }),
+    ))
+}
@@ -0,0 +1,54 @@
+use axum::{http::StatusCode, Extension, Json};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    asset::{AssetParameters, ButtonPress},
+    session::{AMSessionRegistry, SessionError},
+    utils::ExtractSessionId,
+};
+
+use super::AppResponse;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PressButtonRequest {
+    pub asset_id: Uuid,
+    /// Name of the button parameter.
+    pub name: String,
+    /// Whether to return parameters arranged into their folder layout.
+    #[serde(default)]
+    pub tree: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PressButtonResponse {
+    /// The asset's parameters after the button's callback ran.
+    pub parameters: AssetParameters,
+    #[serde(flatten)]
+    pub press: ButtonPress,
+}
+
+pub async fn press_button(
+    Extension(registry): Extension<AMSessionRegistry>,
+    ExtractSessionId(session_id): ExtractSessionId,
+    Json(body): Json<PressButtonRequest>,
+) -> AppResponse<PressButtonResponse> {
+    let mut registry = registry.lock().await;
+    let session = registry
+        .get_session_mut(&session_id)
+        .ok_or(SessionError::SessionNotFound)?;
+
+    // Callbacks can change any parameter, so the whole asset is recorded.
+    let (press, parameters) =
+        session.change_parameters(body.asset_id, "Press button", None, |asset| {
+            let press = asset.press_button(&body.name)?;
+            Ok((press, asset.get_parameters(body.tree)?))
+        })?;
+
+    Ok((
+        StatusCode::OK,
+        Json(PressButtonResponse { parameters, press }),
+    ))
+}
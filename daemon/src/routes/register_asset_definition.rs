@@ -0,0 +1,58 @@
+use std::path::PathBuf;
+
+use axum::{http::StatusCode, Extension, Json};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset::AssetError,
+    asset_dir::{get_asset_config, save_asset_definition},
+    session::{AMSessionRegistry, SessionError},
+    utils::ExtractSessionId,
+};
+
+use super::AppResponse;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterAssetDefinitionRequest {
+    /// Directory to save the new entry in.
+    pub directory: PathBuf,
+    /// Path of the asset library in the daemon-managed asset directory.
+    pub path: PathBuf,
+    pub definition: String,
+    /// Defaults to the label of the asset definition.
+    #[serde(default)]
+    pub display_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterAssetDefinitionResponse {
+    pub id: String,
+}
+
+pub async fn register_asset_definition(
+    Extension(registry): Extension<AMSessionRegistry>,
+    ExtractSessionId(session_id): ExtractSessionId,
+    Json(body): Json<RegisterAssetDefinitionRequest>,
+) -> AppResponse<RegisterAssetDefinitionResponse> {
+    let registry = registry.lock().await;
+    let session = registry
+        .get_session(&session_id)
+        .ok_or(SessionError::SessionNotFound)?;
+
+    let asset_config = get_asset_config(&body.path)?;
+    let definition = session
+        .list_asset_definitions(&asset_config.asset_path)?
+        .into_iter()
+        .find(|definition| definition.name == body.definition)
+        .ok_or_else(|| AssetError::DefinitionNotFound(body.definition.clone()))?;
+
+    let display_name = body.display_name.unwrap_or(definition.label);
+    let id = save_asset_definition(&body.directory, &body.path, &definition.name, &display_name)?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(RegisterAssetDefinitionResponse { id }),
+    ))
+}
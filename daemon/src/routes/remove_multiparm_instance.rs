@@ -0,0 +1,58 @@
+use axum::{http::StatusCode, Extension, Json};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    asset::AssetParameters,
+    session::{AMSessionRegistry, SessionError},
+    utils::ExtractSessionId,
+};
+
+use super::AppResponse;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveMultiparmInstanceRequest {
+    pub asset_id: Uuid,
+    /// Name of the multiparm parameter.
+    pub name: String,
+    /// Zero-based index of the instance to remove.
+    pub index: i32,
+    /// Whether to return parameters arranged into their folder layout.
+    #[serde(default)]
+    pub tree: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveMultiparmInstanceResponse {
+    /// The asset's parameters after the change. Instance parameters are
+    /// renumbered by Houdini, so clients should replace their copy.
+    pub parameters: AssetParameters,
+}
+
+pub async fn remove_multiparm_instance(
+    Extension(registry): Extension<AMSessionRegistry>,
+    ExtractSessionId(session_id): ExtractSessionId,
+    Json(body): Json<RemoveMultiparmInstanceRequest>,
+) -> AppResponse<RemoveMultiparmInstanceResponse> {
+    let mut registry = registry.lock().await;
+    let session = registry
+        .get_session_mut(&session_id)
+        .ok_or(SessionError::SessionNotFound)?;
+
+    let parameters = session.change_parameters(
+        body.asset_id,
+        "Remove multiparm instance",
+        Some(&[body.name.as_str()]),
+        |asset| {
+            asset.remove_multiparm_instance(&body.name, body.index)?;
+            asset.get_parameters(body.tree)
+        },
+    )?;
+
+    Ok((
+        StatusCode::OK,
+        Json(RemoveMultiparmInstanceResponse { parameters }),
+    ))
+}
@@ -0,0 +1,22 @@
+use std::path::PathBuf;
+
+use axum::{http::StatusCode, Json};
+use serde::Deserialize;
+
+use crate::error::AppError;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenamePresetRequest {
+    /// Path of the asset in the daemon-managed asset directory.
+    pub path: PathBuf,
+    pub name: String,
+    pub new_name: String,
+}
+
+pub async fn rename_preset(
+    Json(body): Json<RenamePresetRequest>,
+) -> Result<(StatusCode, Json<()>), AppError> {
+    crate::asset_dir::rename_preset(&body.path, &body.name, &body.new_name)?;
+    Ok((StatusCode::OK, Json(())))
+}
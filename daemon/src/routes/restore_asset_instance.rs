@@ -0,0 +1,79 @@
+use axum::{http::StatusCode, Extension, Json};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    asset::{AssetIoSpec, AssetParameters, ConversionOptions, SerializableAssetInfo},
+    asset_dir::get_asset_config,
+    instance_store::get_instance,
+    session::{AMSessionRegistry, RestoredInstance, SessionError},
+    utils::ExtractSessionId,
+};
+
+use super::AppResponse;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreAssetInstanceRequest {
+    pub key: String,
+    /// Whether to return parameters arranged into their folder layout.
+    #[serde(default)]
+    pub parameter_tree: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreAssetInstanceResponse {
+    pub id: Uuid,
+    pub asset_info: SerializableAssetInfo,
+    pub io_spec: AssetIoSpec,
+    pub parameters: AssetParameters,
+    /// Options to cook the asset with to get the stored result.
+    pub options: ConversionOptions,
+    #[serde(flatten)]
+    pub restored: RestoredInstance,
+}
+
+pub async fn restore_asset_instance(
+    Extension(registry): Extension<AMSessionRegistry>,
+    ExtractSessionId(session_id): ExtractSessionId,
+    Json(body): Json<RestoreAssetInstanceRequest>,
+) -> AppResponse<RestoreAssetInstanceResponse> {
+    let mut registry = registry.lock().await;
+    let session = registry
+        .get_session_mut(&session_id)
+        .ok_or(SessionError::SessionNotFound)?;
+
+    if let Some(asset_id) = session.find_instance(&body.key) {
+        return Err(SessionError::InstanceLoaded {
+            key: body.key,
+            asset_id,
+        }
+        .into());
+    }
+
+    let instance = get_instance(&body.key)?;
+    let asset_config = get_asset_config(&instance.asset_path)?;
+    let (id, restored) = session.restore_instance(&asset_config.asset_path, &instance)?;
+
+    let asset = session
+        .get_asset(id)
+        .ok_or(SessionError::AssetNotFound(id))?;
+    let asset_info = asset.get_asset_info()?;
+    let io_spec = asset.get_io_spec()?;
+    let parameters = asset.get_parameters(body.parameter_tree)?;
+
+    log::debug!("Restored instance {} with ID {id}", instance.key);
+
+    Ok((
+        StatusCode::CREATED,
+        Json(RestoreAssetInstanceResponse {
+            id,
+            asset_info,
+            io_spec,
+            parameters,
+            options: instance.options,
+            restored,
+        }),
+    ))
+}
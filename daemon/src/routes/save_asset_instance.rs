@@ -0,0 +1,35 @@
+use axum::{http::StatusCode, Extension, Json};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    instance_store::{save_instance, AssetInstance},
+    session::{AMSessionRegistry, SessionError},
+    utils::ExtractSessionId,
+};
+
+use super::AppResponse;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveAssetInstanceRequest {
+    pub asset_id: Uuid,
+}
+
+pub async fn save_asset_instance(
+    Extension(registry): Extension<AMSessionRegistry>,
+    ExtractSessionId(session_id): ExtractSessionId,
+    Json(body): Json<SaveAssetInstanceRequest>,
+) -> AppResponse<AssetInstance> {
+    let mut registry = registry.lock().await;
+    let session = registry
+        .get_session_mut(&session_id)
+        .ok_or(SessionError::SessionNotFound)?;
+
+    let instance = session.capture_instance(body.asset_id)?;
+    save_instance(&instance)?;
+
+    log::debug!("Saved asset {} as instance {}", body.asset_id, instance.key);
+
+    Ok((StatusCode::OK, Json(instance)))
+}
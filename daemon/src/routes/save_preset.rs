@@ -0,0 +1,42 @@
+use axum::{http::StatusCode, Extension, Json};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    asset_dir::{self, ParameterPreset},
+    session::{AMSessionRegistry, SessionError},
+    utils::ExtractSessionId,
+};
+
+use super::AppResponse;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavePresetRequest {
+    pub asset_id: Uuid,
+    /// Saving with the name of an existing preset replaces its values.
+    pub name: String,
+}
+
+pub async fn save_preset(
+    Extension(registry): Extension<AMSessionRegistry>,
+    ExtractSessionId(session_id): ExtractSessionId,
+    Json(body): Json<SavePresetRequest>,
+) -> AppResponse<ParameterPreset> {
+    let registry = registry.lock().await;
+    let session = registry
+        .get_session(&session_id)
+        .ok_or(SessionError::SessionNotFound)?;
+
+    let asset = session
+        .get_asset(body.asset_id)
+        .ok_or(SessionError::AssetNotFound(body.asset_id))?;
+    let path = session
+        .get_asset_dir_path(body.asset_id)
+        .ok_or(SessionError::NoAssetDirEntry(body.asset_id))?;
+
+    let values = asset.get_preset_values()?;
+    let preset = asset_dir::save_preset(path, &body.name, values)?;
+
+    Ok((StatusCode::CREATED, Json(preset)))
+}
@@ -0,0 +1,48 @@
+use axum::{http::StatusCode, Extension, Json};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    asset::{InputGeometry, METERS_PER_STUD},
+    session::{AMSessionRegistry, SessionError},
+    utils::ExtractSessionId,
+};
+
+use super::AppResponse;
+
+fn default_units_per_stud() -> f32 {
+    METERS_PER_STUD
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetAssetInputRequest {
+    pub asset_id: Uuid,
+    pub input_index: i32,
+    pub geometry: InputGeometry,
+    /// Houdini units per stud. Defaults to the size of a stud in meters. All
+    /// inputs of an asset must use the same value, which cooked output is
+    /// converted back to studs with.
+    #[serde(default = "default_units_per_stud")]
+    pub units_per_stud: f32,
+}
+
+pub async fn set_asset_input(
+    Extension(registry): Extension<AMSessionRegistry>,
+    ExtractSessionId(session_id): ExtractSessionId,
+    Json(body): Json<SetAssetInputRequest>,
+) -> AppResponse<()> {
+    let mut registry = registry.lock().await;
+    let session = registry
+        .get_session_mut(&session_id)
+        .ok_or(SessionError::SessionNotFound)?;
+
+    session.set_asset_input_geometry(
+        body.asset_id,
+        body.input_index,
+        &body.geometry,
+        body.units_per_stud,
+    )?;
+
+    Ok((StatusCode::OK, Json(())))
+}
@@ -0,0 +1,54 @@
+use axum::{http::StatusCode, Extension, Json};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    asset::ParameterState,
+    session::{AMSessionRegistry, SessionError},
+    utils::ExtractSessionId,
+};
+
+use super::AppResponse;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetParameterExpressionRequest {
+    pub asset_id: Uuid,
+    pub name: String,
+    /// Component of the parameter's tuple.
+    #[serde(default)]
+    pub index: i32,
+    /// The new expression. The expression is removed if this isn't set.
+    pub expression: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetParameterExpressionResponse {
+    /// Effective visibility and enabled state of the parameter, and of any
+    /// parameter whose state changed because of the new expression.
+    pub states: Vec<ParameterState>,
+}
+
+pub async fn set_parameter_expression(
+    Extension(registry): Extension<AMSessionRegistry>,
+    ExtractSessionId(session_id): ExtractSessionId,
+    Json(body): Json<SetParameterExpressionRequest>,
+) -> AppResponse<SetParameterExpressionResponse> {
+    let mut registry = registry.lock().await;
+    let session = registry
+        .get_session_mut(&session_id)
+        .ok_or(SessionError::SessionNotFound)?;
+
+    let states = session.change_parameters(
+        body.asset_id,
+        "Set expression",
+        Some(&[body.name.as_str()]),
+        |asset| asset.set_parameter_expression(&body.name, body.index, body.expression.as_deref()),
+    )?;
+
+    Ok((
+        StatusCode::OK,
+        Json(SetParameterExpressionResponse { states }),
+    ))
+}
@@ -0,0 +1,51 @@
+use axum::{http::StatusCode, Extension, Json};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    asset::{ParameterState, ParameterWrite, WriteOptions},
+    session::{AMSessionRegistry, SessionError},
+    utils::ExtractSessionId,
+};
+
+use super::AppResponse;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetParametersRequest {
+    pub asset_id: Uuid,
+    pub values: Vec<ParameterWrite>,
+    #[serde(flatten)]
+    pub options: WriteOptions,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetParametersResponse {
+    /// Effective visibility and enabled state of the written parameters, and
+    /// of any parameter whose state changed because of the write.
+    pub states: Vec<ParameterState>,
+}
+
+pub async fn set_parameters(
+    Extension(registry): Extension<AMSessionRegistry>,
+    ExtractSessionId(session_id): ExtractSessionId,
+    Json(body): Json<SetParametersRequest>,
+) -> AppResponse<SetParametersResponse> {
+    let mut registry = registry.lock().await;
+    let session = registry
+        .get_session_mut(&session_id)
+        .ok_or(SessionError::SessionNotFound)?;
+
+    let names: Vec<&str> = body
+        .values
+        .iter()
+        .map(|write| write.name.as_str())
+        .collect();
+    let states =
+        session.change_parameters(body.asset_id, "Set parameters", Some(&names), |asset| {
+            asset.set_parameters(&body.values, &body.options)
+        })?;
+
+    Ok((StatusCode::OK, Json(SetParametersResponse { states })))
+}
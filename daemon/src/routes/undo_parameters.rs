@@ -0,0 +1,31 @@
+use axum::{http::StatusCode, Extension, Json};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    asset::AppliedPreset,
+    session::{AMSessionRegistry, SessionError},
+    utils::ExtractSessionId,
+};
+
+use super::AppResponse;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoParametersRequest {
+    pub asset_id: Uuid,
+}
+
+pub async fn undo_parameters(
+    Extension(registry): Extension<AMSessionRegistry>,
+    ExtractSessionId(session_id): ExtractSessionId,
+    Json(body): Json<UndoParametersRequest>,
+) -> AppResponse<AppliedPreset> {
+    let mut registry = registry.lock().await;
+    let session = registry
+        .get_session_mut(&session_id)
+        .ok_or(SessionError::SessionNotFound)?;
+
+    let applied = session.undo_parameters(body.asset_id)?;
+    Ok((StatusCode::OK, Json(applied)))
+}
@@ -0,0 +1,169 @@
+/// This module tracks how loaded assets are wired together inside a session,
+/// so the output of one asset can feed an input of another without the
+/// geometry leaving Houdini.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+use uuid::Uuid;
+
+/// A connection from an output of one asset to an input of another.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetConnection {
+    pub source_asset_id: Uuid,
+    pub output_index: i32,
+    pub target_asset_id: Uuid,
+    pub input_index: i32,
+}
+
+#[derive(Debug, Default)]
+pub struct AssetGraph {
+    connections: Vec<AssetConnection>,
+}
+
+impl AssetGraph {
+    pub fn connections(&self) -> &[AssetConnection] {
+        &self.connections
+    }
+
+    /// Returns the connection feeding an asset input, if any.
+    pub fn get_input(&self, target_asset_id: Uuid, input_index: i32) -> Option<&AssetConnection> {
+        self.connections.iter().find(|connection| {
+            connection.target_asset_id == target_asset_id && connection.input_index == input_index
+        })
+    }
+
+    /// Whether connecting `source_asset_id` into `target_asset_id` would make
+    /// an asset depend on its own output.
+    pub fn would_cycle(&self, source_asset_id: Uuid, target_asset_id: Uuid) -> bool {
+        source_asset_id == target_asset_id
+            || self.upstream_of(source_asset_id).contains(&target_asset_id)
+    }
+
+    /// Adds a connection, replacing whatever was connected to the same input.
+    pub fn insert(&mut self, connection: AssetConnection) {
+        self.remove_input(connection.target_asset_id, connection.input_index);
+        self.connections.push(connection);
+    }
+
+    /// Removes the connection feeding an asset input and returns it.
+    pub fn remove_input(
+        &mut self,
+        target_asset_id: Uuid,
+        input_index: i32,
+    ) -> Option<AssetConnection> {
+        let position = self.connections.iter().position(|connection| {
+            connection.target_asset_id == target_asset_id && connection.input_index == input_index
+        })?;
+
+        Some(self.connections.remove(position))
+    }
+
+    /// Removes every connection to and from an asset.
+    pub fn remove_asset(&mut self, asset_id: Uuid) {
+        self.connections.retain(|connection| {
+            connection.source_asset_id != asset_id && connection.target_asset_id != asset_id
+        });
+    }
+
+    /// Returns every asset the given asset depends on, ordered so that each
+    /// asset comes after all of its own upstream assets.
+    pub fn upstream_of(&self, asset_id: Uuid) -> Vec<Uuid> {
+        let mut order = Vec::new();
+        let mut visited = HashSet::new();
+
+        // Iterative post-order walk, so long chains can't overflow the stack.
+        let mut stack = vec![(asset_id, false)];
+        while let Some((current, expanded)) = stack.pop() {
+            if expanded {
+                if current != asset_id {
+                    order.push(current);
+                }
+                continue;
+            }
+
+            if !visited.insert(current) {
+                continue;
+            }
+
+            stack.push((current, true));
+            for connection in self.connections.iter().rev() {
+                if connection.target_asset_id == current {
+                    stack.push((connection.source_asset_id, false));
+                }
+            }
+        }
+
+        order
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connection(source: Uuid, target: Uuid, input_index: i32) -> AssetConnection {
+        AssetConnection {
+            source_asset_id: source,
+            output_index: 0,
+            target_asset_id: target,
+            input_index,
+        }
+    }
+
+    #[test]
+    fn rejects_cycles() {
+        let [a, b, c] = [(); 3].map(|_| Uuid::new_v4());
+        let mut graph = AssetGraph::default();
+        graph.insert(connection(a, b, 0));
+        graph.insert(connection(b, c, 0));
+
+        assert!(graph.would_cycle(a, a));
+        assert!(graph.would_cycle(c, a));
+        assert!(graph.would_cycle(b, a));
+        assert!(!graph.would_cycle(a, c));
+    }
+
+    #[test]
+    fn insert_replaces_the_same_input() {
+        let [a, b, c] = [(); 3].map(|_| Uuid::new_v4());
+        let mut graph = AssetGraph::default();
+        graph.insert(connection(a, c, 0));
+        graph.insert(connection(b, c, 1));
+        graph.insert(connection(b, c, 0));
+
+        assert_eq!(graph.connections().len(), 2);
+        assert_eq!(graph.get_input(c, 0), Some(&connection(b, c, 0)));
+        assert_eq!(graph.get_input(c, 1), Some(&connection(b, c, 1)));
+        assert!(graph.upstream_of(c).iter().all(|&asset| asset != a));
+    }
+
+    #[test]
+    fn upstream_assets_come_before_their_dependents() {
+        let [a, b, c, d] = [(); 4].map(|_| Uuid::new_v4());
+        let mut graph = AssetGraph::default();
+        graph.insert(connection(a, b, 0));
+        graph.insert(connection(a, c, 0));
+        graph.insert(connection(b, d, 0));
+        graph.insert(connection(c, d, 1));
+
+        let upstream = graph.upstream_of(d);
+        let position = |asset| upstream.iter().position(|&u| u == asset).unwrap();
+        assert_eq!(upstream.len(), 3);
+        assert!(position(a) < position(b));
+        assert!(position(a) < position(c));
+    }
+
+    #[test]
+    fn remove_asset_drops_its_connections() {
+        let [a, b, c] = [(); 3].map(|_| Uuid::new_v4());
+        let mut graph = AssetGraph::default();
+        graph.insert(connection(a, b, 0));
+        graph.insert(connection(b, c, 0));
+
+        graph.remove_asset(b);
+        assert!(graph.connections().is_empty());
+        assert_eq!(graph.remove_input(c, 0), None);
+    }
+}
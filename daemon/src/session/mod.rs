@@ -1,5 +1,10 @@
+mod asset_graph;
+mod param_history;
 mod session;
 mod session_registry;
 
-pub use session::{Options, Session, SessionError, SessionInfo, SessionType};
+pub use asset_graph::{AssetConnection, AssetGraph};
+pub use session::{
+    HistoryInfo, Options, RestoredInstance, Session, SessionError, SessionInfo, SessionType,
+};
 pub use session_registry::{AMSessionRegistry, SessionRegistry};
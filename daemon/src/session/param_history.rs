@@ -0,0 +1,366 @@
+/// This module keeps the undo and redo history of parameter changes for each
+/// loaded asset, since Studio's own undo doesn't know about Houdini state.
+///
+/// Each entry stores the values and expressions of the changed parameters
+/// before and after a batch of writes. Undoing re-applies the snapshot from
+/// before, and redoing re-applies the snapshot from after. The history is
+/// bounded, dropping the oldest entries first.
+///
+/// Dragging a slider sends a stream of writes to the same parameter, so
+/// consecutive changes to the same parameters are merged into one entry.
+
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+use crate::asset::ParameterSnapshot;
+
+/// Number of changes that can be undone for each asset.
+const MAX_ENTRIES: usize = 100;
+/// Longest pause between two changes that are merged into one entry.
+const MERGE_WINDOW_MS: i64 = 1500;
+
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    pub label: String,
+    pub date: DateTime<Utc>,
+    /// The changed parameters before the change. Parameters that didn't exist
+    /// yet, such as new multiparm instances, are left out.
+    pub before: ParameterSnapshot,
+    /// The changed parameters after the change.
+    pub after: ParameterSnapshot,
+}
+
+/// Returns the parts of a snapshot that belong to changed parameters.
+fn changed_part(
+    snapshot: &ParameterSnapshot,
+    changed: impl Fn(&String) -> bool,
+) -> ParameterSnapshot {
+    ParameterSnapshot {
+        values: snapshot
+            .values
+            .iter()
+            .filter(|(name, _)| changed(name))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect(),
+        expressions: snapshot
+            .expressions
+            .iter()
+            .filter(|(name, _)| changed(name))
+            .map(|(name, expressions)| (name.clone(), expressions.clone()))
+            .collect(),
+    }
+}
+
+impl HistoryEntry {
+    /// Builds an entry from snapshots of the parameters touched by a change,
+    /// taken before and after it. Returns `None` if nothing changed.
+    pub fn from_snapshots(
+        label: &str,
+        before: &ParameterSnapshot,
+        after: &ParameterSnapshot,
+    ) -> Option<Self> {
+        let changed = |name: &String| {
+            before.values.get(name) != after.values.get(name)
+                || before.expressions.get(name) != after.expressions.get(name)
+        };
+        let entry = Self {
+            label: label.to_owned(),
+            date: Utc::now(),
+            before: changed_part(before, changed),
+            after: changed_part(after, changed),
+        };
+
+        (!entry.is_empty()).then_some(entry)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.before.values.is_empty() && self.after.values.is_empty()
+    }
+
+    fn parameters(&self) -> Vec<String> {
+        let mut parameters: Vec<String> = self
+            .before
+            .values
+            .keys()
+            .chain(self.after.values.keys())
+            .cloned()
+            .collect();
+        parameters.sort();
+        parameters.dedup();
+        parameters
+    }
+
+    /// Whether `next` continues this change, such as the next step of a
+    /// slider being dragged.
+    fn continues_with(&self, next: &Self) -> bool {
+        self.label == next.label
+            && next.date - self.date <= Duration::milliseconds(MERGE_WINDOW_MS)
+            && self.parameters() == next.parameters()
+    }
+}
+
+/// A history entry as listed to the client.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntrySummary {
+    pub label: String,
+    pub date: DateTime<Utc>,
+    /// Names of the parameters changed by the entry.
+    pub parameters: Vec<String>,
+}
+
+impl From<&HistoryEntry> for HistoryEntrySummary {
+    fn from(entry: &HistoryEntry) -> Self {
+        Self {
+            label: entry.label.clone(),
+            date: entry.date,
+            parameters: entry.parameters(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ParameterHistory {
+    undo: VecDeque<HistoryEntry>,
+    redo: Vec<HistoryEntry>,
+}
+
+impl ParameterHistory {
+    /// Records a new change, merging it into the latest one if it continues
+    /// it. Changes that were undone can no longer be redone.
+    pub fn push(&mut self, entry: HistoryEntry) {
+        let continued = self.redo.is_empty()
+            && self
+                .undo
+                .back()
+                .map_or(false, |latest| latest.continues_with(&entry));
+        if continued {
+            if let Some(latest) = self.undo.back_mut() {
+                latest.date = entry.date;
+                latest.after = entry.after;
+                // Dragging a slider back to where it started undoes nothing.
+                if latest.before == latest.after {
+                    self.undo.pop_back();
+                }
+            }
+            return;
+        }
+
+        self.redo.clear();
+        self.undo.push_back(entry);
+        if self.undo.len() > MAX_ENTRIES {
+            self.undo.pop_front();
+        }
+    }
+
+    /// Returns the change to undo, without removing it.
+    pub fn peek_undo(&self) -> Option<&HistoryEntry> {
+        self.undo.back()
+    }
+
+    /// Returns the change to redo, without removing it.
+    pub fn peek_redo(&self) -> Option<&HistoryEntry> {
+        self.redo.last()
+    }
+
+    /// Moves the latest change to the redo stack once it has been undone.
+    pub fn commit_undo(&mut self) {
+        if let Some(entry) = self.undo.pop_back() {
+            self.redo.push(entry);
+        }
+    }
+
+    /// Moves the latest undone change back once it has been redone.
+    pub fn commit_redo(&mut self) {
+        if let Some(entry) = self.redo.pop() {
+            self.undo.push_back(entry);
+        }
+    }
+
+    /// Changes that can be undone, oldest first.
+    pub fn undo_entries(&self) -> Vec<HistoryEntrySummary> {
+        self.undo.iter().map(HistoryEntrySummary::from).collect()
+    }
+
+    /// Changes that can be redone, next first.
+    pub fn redo_entries(&self) -> Vec<HistoryEntrySummary> {
+        self.redo
+            .iter()
+            .rev()
+            .map(HistoryEntrySummary::from)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset::ParamValue;
+
+    fn snapshot(values: &[(&str, f32)]) -> ParameterSnapshot {
+        ParameterSnapshot {
+            values: values
+                .iter()
+                .map(|&(name, value)| {
+                    let value = ParamValue::Float {
+                        current: vec![value],
+                    };
+                    (name.to_owned(), value)
+                })
+                .collect(),
+            expressions: Default::default(),
+        }
+    }
+
+    fn entry(label: &str, before: &[(&str, f32)], after: &[(&str, f32)]) -> HistoryEntry {
+        HistoryEntry::from_snapshots(label, &snapshot(before), &snapshot(after))
+            .expect("snapshots differ")
+    }
+
+    fn later(mut entry: HistoryEntry, after: &HistoryEntry, ms: i64) -> HistoryEntry {
+        entry.date = after.date + Duration::milliseconds(ms);
+        entry
+    }
+
+    fn labels(history: &ParameterHistory) -> Vec<String> {
+        history
+            .undo_entries()
+            .into_iter()
+            .map(|entry| entry.label)
+            .collect()
+    }
+
+    #[test]
+    fn skips_unchanged_snapshots() {
+        let unchanged = snapshot(&[("size", 1.0)]);
+        assert!(HistoryEntry::from_snapshots("Set size", &unchanged, &unchanged).is_none());
+    }
+
+    #[test]
+    fn merges_changes_within_the_window() {
+        let mut history = ParameterHistory::default();
+        let first = entry("Set size", &[("size", 0.0)], &[("size", 1.0)]);
+        let second = later(
+            entry("Set size", &[("size", 1.0)], &[("size", 2.0)]),
+            &first,
+            MERGE_WINDOW_MS - 100,
+        );
+        history.push(first);
+        history.push(second);
+
+        assert_eq!(history.undo_entries().len(), 1);
+        let merged = history.peek_undo().unwrap();
+        assert_eq!(merged.before, snapshot(&[("size", 0.0)]));
+        assert_eq!(merged.after, snapshot(&[("size", 2.0)]));
+    }
+
+    #[test]
+    fn merging_back_to_the_start_drops_the_entry() {
+        let mut history = ParameterHistory::default();
+        let first = entry("Set size", &[("size", 0.0)], &[("size", 1.0)]);
+        let second = later(
+            entry("Set size", &[("size", 1.0)], &[("size", 0.0)]),
+            &first,
+            100,
+        );
+        history.push(first);
+        history.push(second);
+
+        assert!(history.peek_undo().is_none());
+    }
+
+    #[test]
+    fn keeps_changes_apart_outside_the_window() {
+        let mut history = ParameterHistory::default();
+        let first = entry("Set size", &[("size", 0.0)], &[("size", 1.0)]);
+        let second = later(
+            entry("Set size", &[("size", 1.0)], &[("size", 2.0)]),
+            &first,
+            MERGE_WINDOW_MS + 100,
+        );
+        history.push(first);
+        history.push(second);
+
+        assert_eq!(history.undo_entries().len(), 2);
+    }
+
+    #[test]
+    fn keeps_changes_apart_across_labels_and_parameters() {
+        let mut history = ParameterHistory::default();
+        let first = entry("Set size", &[("size", 0.0)], &[("size", 1.0)]);
+        let other_label = later(
+            entry("Apply preset", &[("size", 1.0)], &[("size", 2.0)]),
+            &first,
+            100,
+        );
+        let other_parameters = later(
+            entry(
+                "Apply preset",
+                &[("size", 2.0), ("seed", 0.0)],
+                &[("size", 3.0), ("seed", 1.0)],
+            ),
+            &other_label,
+            100,
+        );
+        history.push(first);
+        history.push(other_label);
+        history.push(other_parameters);
+
+        assert_eq!(
+            labels(&history),
+            ["Set size", "Apply preset", "Apply preset"]
+        );
+    }
+
+    #[test]
+    fn drops_the_oldest_entries() {
+        let mut history = ParameterHistory::default();
+        for index in 0..MAX_ENTRIES + 5 {
+            let label = format!("Change {index}");
+            history.push(entry(&label, &[("size", 0.0)], &[("size", 1.0)]));
+        }
+
+        let labels = labels(&history);
+        assert_eq!(labels.len(), MAX_ENTRIES);
+        assert_eq!(labels[0], "Change 5");
+    }
+
+    #[test]
+    fn push_clears_redo() {
+        let mut history = ParameterHistory::default();
+        history.push(entry("Set size", &[("size", 0.0)], &[("size", 1.0)]));
+        history.commit_undo();
+        assert!(history.peek_redo().is_some());
+
+        history.push(entry("Set seed", &[("seed", 0.0)], &[("seed", 1.0)]));
+        assert!(history.peek_redo().is_none());
+        assert_eq!(labels(&history), ["Set seed"]);
+    }
+
+    #[test]
+    fn undo_and_redo_round_trip() {
+        let mut history = ParameterHistory::default();
+        history.push(entry("Set size", &[("size", 0.0)], &[("size", 1.0)]));
+        history.push(entry("Set seed", &[("seed", 0.0)], &[("seed", 1.0)]));
+
+        history.commit_undo();
+        assert_eq!(history.peek_undo().unwrap().label, "Set size");
+        assert_eq!(history.peek_redo().unwrap().label, "Set seed");
+
+        history.commit_undo();
+        assert!(history.peek_undo().is_none());
+        let redo: Vec<String> = history
+            .redo_entries()
+            .into_iter()
+            .map(|entry| entry.label)
+            .collect();
+        assert_eq!(redo, ["Set size", "Set seed"]);
+
+        history.commit_redo();
+        history.commit_redo();
+        assert!(history.peek_redo().is_none());
+        assert_eq!(labels(&history), ["Set size", "Set seed"]);
+    }
+}
@@ -1,10 +1,11 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fs,
     net::SocketAddrV4,
     path::{Path, PathBuf},
 };
 
+use chrono::Utc;
 use hapi_rs::session::{
     self, ConnectionType, License, Session as HoudiniSession, SessionOptionsBuilder,
     SessionType as HapiSessionType, StatusVerbosity,
@@ -13,9 +14,18 @@ use serde::Serialize;
 use thiserror::Error;
 use uuid::Uuid;
 
+use super::{
+    asset_graph::{AssetConnection, AssetGraph},
+    param_history::{HistoryEntry, HistoryEntrySummary, ParameterHistory},
+};
 use crate::{
-    asset::{Asset, AssetError},
-    utils::set_hapi_env_variables, state::{DaemonState, StateError},
+    asset::{
+        AppliedPreset, Asset, AssetDefinition, AssetError, ConversionOptions, CookResult,
+        InputGeometry,
+    },
+    instance_store::{self, AssetInstance, InputSource, InstanceInput},
+    state::{DaemonState, StateError},
+    utils::set_hapi_env_variables,
 };
 
 #[derive(Debug, Error, Serialize)]
@@ -41,10 +51,31 @@ pub enum SessionError {
     #[error("Failed to remove pipe file")]
     RemovePipeFileError,
 
+    #[error("No session found")]
+    SessionNotFound,
+    #[error("No asset found with ID {0}")]
+    AssetNotFound(Uuid),
+    #[error("Connecting these assets would create a cycle")]
+    AssetCycle,
+    #[error("Input {input_index} of asset {asset_id} is not connected to an asset")]
+    ConnectionNotFound { asset_id: Uuid, input_index: i32 },
+    #[error("Asset {0} was not loaded from the asset directory")]
+    NoAssetDirEntry(Uuid),
+    #[error("Asset {0} has no parameter changes to undo")]
+    NothingToUndo(Uuid),
+    #[error("Asset {0} has no parameter changes to redo")]
+    NothingToRedo(Uuid),
+    #[error("Input {input_index} of asset {asset_id} is fed by an asset that isn't stored as an instance")]
+    InputNotStored { asset_id: Uuid, input_index: i32 },
+    #[error("Instance {key} is already loaded as asset {asset_id}")]
+    InstanceLoaded { key: String, asset_id: Uuid },
+
     #[error(transparent)]
     NewAssetError(AssetError),
     #[error(transparent)]
-    StateError(StateError)
+    AssetError(AssetError),
+    #[error(transparent)]
+    StateError(StateError),
 }
 
 type Result<T> = std::result::Result<T, SessionError>;
@@ -71,6 +102,32 @@ pub struct SessionInfo {
     pub pipe_path: Option<PathBuf>,
 }
 
+/// The parameter history of an asset, as listed to the client.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryInfo {
+    /// Changes that can be undone, oldest first.
+    pub undo: Vec<HistoryEntrySummary>,
+    /// Changes that can be redone, next first.
+    pub redo: Vec<HistoryEntrySummary>,
+    /// Whether parameters changed since the asset was last cooked.
+    pub needs_cook: bool,
+}
+
+/// The result of restoring a stored instance onto a loaded asset.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoredInstance {
+    /// Stored parameters that the asset no longer has.
+    pub skipped: Vec<String>,
+    /// Inputs fed by instances that aren't loaded in the session. They are
+    /// connected once those instances are restored.
+    pub missing_inputs: Vec<i32>,
+    /// Inputs of loaded assets that were waiting for this instance, and are
+    /// now connected to it.
+    pub connected_downstream: Vec<AssetConnection>,
+}
+
 pub struct Session {
     pub session_id: Uuid,
 
@@ -81,6 +138,23 @@ pub struct Session {
     houdini_session: HoudiniSession,
     pipe_path: Option<PathBuf>,
     asset_db: HashMap<Uuid, Asset>,
+    /// Path in the daemon-managed asset directory that each asset was loaded
+    /// from.
+    asset_dir_paths: HashMap<Uuid, PathBuf>,
+    asset_graph: AssetGraph,
+    param_histories: HashMap<Uuid, ParameterHistory>,
+    /// Assets whose parameters changed since they were last cooked.
+    needs_cook: HashSet<Uuid>,
+    /// Key of each asset that was stored as, or restored from, an instance.
+    instance_keys: HashMap<Uuid, String>,
+    /// Geometry sent by the plugin for each asset input, kept so the asset
+    /// can be stored as an instance.
+    input_geometries: HashMap<(Uuid, i32), (InputGeometry, f32)>,
+    /// Conversion options of each asset's latest cook.
+    cook_options: HashMap<Uuid, ConversionOptions>,
+    /// Inputs of restored instances that are fed by an instance that isn't
+    /// loaded yet, with that instance's key and output index.
+    waiting_inputs: HashMap<(Uuid, i32), (String, i32)>,
 }
 
 impl Session {
@@ -152,6 +226,14 @@ impl Session {
             houdini_session,
             pipe_path,
             asset_db: HashMap::new(),
+            asset_dir_paths: HashMap::new(),
+            asset_graph: AssetGraph::default(),
+            param_histories: HashMap::new(),
+            needs_cook: HashSet::new(),
+            instance_keys: HashMap::new(),
+            input_geometries: HashMap::new(),
+            cook_options: HashMap::new(),
+            waiting_inputs: HashMap::new(),
         })
     }
 
@@ -213,8 +295,12 @@ impl Session {
         self.asset_db.get(&asset_id)
     }
 
-    pub fn load_asset_file<P: AsRef<Path>>(&mut self, path: &P) -> Result<Uuid> {
-        let asset = Asset::new_from_path(&self.houdini_session, path)
+    pub fn load_asset_file<P: AsRef<Path>>(
+        &mut self,
+        path: &P,
+        definition: Option<&str>,
+    ) -> Result<Uuid> {
+        let asset = Asset::new_from_path(&self.houdini_session, path, definition)
             .map_err(SessionError::NewAssetError)?;
 
         let asset_id = Uuid::new_v4();
@@ -222,4 +308,472 @@ impl Session {
 
         Ok(asset_id)
     }
+
+    /// Records the asset directory entry an asset was loaded from.
+    pub fn set_asset_dir_path(&mut self, asset_id: Uuid, path: PathBuf) {
+        self.asset_dir_paths.insert(asset_id, path);
+    }
+
+    pub fn get_asset_dir_path(&self, asset_id: Uuid) -> Option<&Path> {
+        self.asset_dir_paths.get(&asset_id).map(PathBuf::as_path)
+    }
+
+    /// Returns every asset definition in a library file.
+    pub fn list_asset_definitions<P: AsRef<Path>>(&self, path: &P) -> Result<Vec<AssetDefinition>> {
+        Asset::list_definitions(&self.houdini_session, path).map_err(SessionError::AssetError)
+    }
+
+    fn try_get_asset(&self, asset_id: Uuid) -> Result<&Asset> {
+        self.get_asset(asset_id)
+            .ok_or(SessionError::AssetNotFound(asset_id))
+    }
+
+    /// Returns every connection between assets in the session.
+    pub fn asset_connections(&self) -> &[AssetConnection] {
+        self.asset_graph.connections()
+    }
+
+    /// Connects an output of one loaded asset to an input of another. Any
+    /// asset or geometry previously connected to that input is replaced.
+    pub fn connect_assets(&mut self, connection: AssetConnection) -> Result<()> {
+        let source = self.try_get_asset(connection.source_asset_id)?;
+        let target = self.try_get_asset(connection.target_asset_id)?;
+
+        if self
+            .asset_graph
+            .would_cycle(connection.source_asset_id, connection.target_asset_id)
+        {
+            return Err(SessionError::AssetCycle);
+        }
+
+        target
+            .connect_asset_input(connection.input_index, source, connection.output_index)
+            .map_err(SessionError::AssetError)?;
+
+        self.input_geometries
+            .remove(&(connection.target_asset_id, connection.input_index));
+        self.waiting_inputs
+            .remove(&(connection.target_asset_id, connection.input_index));
+        self.asset_graph.insert(connection);
+        Ok(())
+    }
+
+    /// Disconnects the asset connected to an input of another asset.
+    pub fn disconnect_assets(&mut self, target_asset_id: Uuid, input_index: i32) -> Result<()> {
+        let target = self.try_get_asset(target_asset_id)?;
+        // An input waiting for an instance isn't connected in Houdini yet.
+        if self
+            .waiting_inputs
+            .remove(&(target_asset_id, input_index))
+            .is_some()
+        {
+            return Ok(());
+        }
+        if self
+            .asset_graph
+            .get_input(target_asset_id, input_index)
+            .is_none()
+        {
+            return Err(SessionError::ConnectionNotFound {
+                asset_id: target_asset_id,
+                input_index,
+            });
+        }
+
+        target
+            .disconnect_input(input_index)
+            .map_err(SessionError::AssetError)?;
+
+        self.asset_graph.remove_input(target_asset_id, input_index);
+        Ok(())
+    }
+
+    /// Sets the geometry connected to an asset input, replacing any asset
+    /// that was connected to it.
+    pub fn set_asset_input_geometry(
+        &mut self,
+        asset_id: Uuid,
+        input_index: i32,
+        geometry: &InputGeometry,
+        units_per_stud: f32,
+    ) -> Result<()> {
+        let asset = self
+            .asset_db
+            .get_mut(&asset_id)
+            .ok_or(SessionError::AssetNotFound(asset_id))?;
+        asset
+            .set_input_geometry(input_index, geometry, units_per_stud)
+            .map_err(SessionError::AssetError)?;
+
+        self.asset_graph.remove_input(asset_id, input_index);
+        self.waiting_inputs.remove(&(asset_id, input_index));
+        self.input_geometries
+            .insert((asset_id, input_index), (geometry.clone(), units_per_stud));
+        Ok(())
+    }
+
+    /// Cooks an asset and converts its geometry. Every asset upstream of it is
+    /// cooked first, in dependency order.
+    pub fn cook_asset(
+        &mut self,
+        asset_id: Uuid,
+        options: &ConversionOptions,
+    ) -> Result<CookResult> {
+        let asset = self.try_get_asset(asset_id)?;
+
+        let upstream_ids = self.asset_graph.upstream_of(asset_id);
+        for &upstream_id in &upstream_ids {
+            log::debug!("Cooking upstream asset {upstream_id} of {asset_id}");
+            self.try_get_asset(upstream_id)?
+                .cook()
+                .map_err(SessionError::AssetError)?;
+        }
+
+        let cook_result = asset
+            .cook_asset(options)
+            .map_err(SessionError::AssetError)?;
+        for cooked_id in upstream_ids.iter().chain([&asset_id]) {
+            self.needs_cook.remove(cooked_id);
+        }
+        self.cook_options.insert(asset_id, options.clone());
+
+        Ok(cook_result)
+    }
+
+    /// Runs a change to an asset's parameters and records it in the asset's
+    /// history, so it can be undone.
+    ///
+    /// Only the `parameters` the change touches are recorded, together with
+    /// the instances of touched multiparms. Changes that can touch anything,
+    /// such as button callbacks, record every parameter by passing `None`.
+    pub fn change_parameters<T>(
+        &mut self,
+        asset_id: Uuid,
+        label: &str,
+        parameters: Option<&[&str]>,
+        change: impl FnOnce(&Asset) -> std::result::Result<T, AssetError>,
+    ) -> Result<T> {
+        let asset = self.try_get_asset(asset_id)?;
+
+        let before = asset
+            .snapshot_parameters(parameters)
+            .map_err(SessionError::AssetError)?;
+        let result = change(asset).map_err(SessionError::AssetError)?;
+        let after = asset
+            .snapshot_parameters(parameters)
+            .map_err(SessionError::AssetError)?;
+
+        if let Some(entry) = HistoryEntry::from_snapshots(label, &before, &after) {
+            self.param_histories
+                .entry(asset_id)
+                .or_default()
+                .push(entry);
+            self.needs_cook.insert(asset_id);
+        }
+
+        Ok(result)
+    }
+
+    /// Restores the parameter values from before the latest change.
+    pub fn undo_parameters(&mut self, asset_id: Uuid) -> Result<AppliedPreset> {
+        let asset = self
+            .asset_db
+            .get(&asset_id)
+            .ok_or(SessionError::AssetNotFound(asset_id))?;
+        let history = self
+            .param_histories
+            .get_mut(&asset_id)
+            .ok_or(SessionError::NothingToUndo(asset_id))?;
+        let entry = history
+            .peek_undo()
+            .ok_or(SessionError::NothingToUndo(asset_id))?;
+
+        let applied = asset
+            .restore_snapshot(&entry.before)
+            .map_err(SessionError::AssetError)?;
+        history.commit_undo();
+        self.needs_cook.insert(asset_id);
+
+        Ok(applied)
+    }
+
+    /// Re-applies the parameter values of the latest undone change.
+    pub fn redo_parameters(&mut self, asset_id: Uuid) -> Result<AppliedPreset> {
+        let asset = self
+            .asset_db
+            .get(&asset_id)
+            .ok_or(SessionError::AssetNotFound(asset_id))?;
+        let history = self
+            .param_histories
+            .get_mut(&asset_id)
+            .ok_or(SessionError::NothingToRedo(asset_id))?;
+        let entry = history
+            .peek_redo()
+            .ok_or(SessionError::NothingToRedo(asset_id))?;
+
+        let applied = asset
+            .restore_snapshot(&entry.after)
+            .map_err(SessionError::AssetError)?;
+        history.commit_redo();
+        self.needs_cook.insert(asset_id);
+
+        Ok(applied)
+    }
+
+    /// Returns the loaded asset that was stored as, or restored from, an
+    /// instance.
+    pub fn find_instance(&self, key: &str) -> Option<Uuid> {
+        self.instance_keys
+            .iter()
+            .find(|(_, instance_key)| *instance_key == key)
+            .map(|(asset_id, _)| *asset_id)
+    }
+
+    /// Captures a loaded asset as an instance to be stored. The asset keeps
+    /// its key once it has one, so storing it again replaces the instance.
+    ///
+    /// Inputs fed by another asset refer to it by its key, so that asset must
+    /// be stored first.
+    pub fn capture_instance(&mut self, asset_id: Uuid) -> Result<AssetInstance> {
+        let asset = self.try_get_asset(asset_id)?;
+        let asset_path = self
+            .asset_dir_paths
+            .get(&asset_id)
+            .cloned()
+            .ok_or(SessionError::NoAssetDirEntry(asset_id))?;
+        let definition = asset.definition_name().map_err(SessionError::AssetError)?;
+        let parameters = asset
+            .snapshot_parameters(None)
+            .map_err(SessionError::AssetError)?;
+
+        let mut inputs = Vec::new();
+        for ((target_asset_id, input_index), (geometry, units_per_stud)) in &self.input_geometries {
+            if *target_asset_id == asset_id {
+                inputs.push(InstanceInput {
+                    input_index: *input_index,
+                    source: InputSource::Geometry {
+                        geometry: geometry.clone(),
+                        units_per_stud: *units_per_stud,
+                    },
+                });
+            }
+        }
+        for connection in self.asset_graph.connections() {
+            if connection.target_asset_id != asset_id {
+                continue;
+            }
+
+            let instance_key = self.instance_keys.get(&connection.source_asset_id).ok_or(
+                SessionError::InputNotStored {
+                    asset_id,
+                    input_index: connection.input_index,
+                },
+            )?;
+            inputs.push(InstanceInput {
+                input_index: connection.input_index,
+                source: InputSource::Asset {
+                    instance_key: instance_key.clone(),
+                    output_index: connection.output_index,
+                },
+            });
+        }
+        for ((target_asset_id, input_index), (instance_key, output_index)) in &self.waiting_inputs {
+            if *target_asset_id == asset_id {
+                inputs.push(InstanceInput {
+                    input_index: *input_index,
+                    source: InputSource::Asset {
+                        instance_key: instance_key.clone(),
+                        output_index: *output_index,
+                    },
+                });
+            }
+        }
+        inputs.sort_by_key(|input| input.input_index);
+
+        let options = self
+            .cook_options
+            .get(&asset_id)
+            .cloned()
+            .unwrap_or_default();
+        let key = self
+            .instance_keys
+            .entry(asset_id)
+            .or_insert_with(instance_store::new_key)
+            .clone();
+
+        Ok(AssetInstance {
+            key,
+            asset_path,
+            definition,
+            date_modified: Utc::now(),
+            parameters,
+            inputs,
+            options,
+        })
+    }
+
+    /// Loads the asset of a stored instance and restores its parameters and
+    /// inputs. Inputs fed by instances that aren't loaded wait until those
+    /// instances are restored, and inputs that were waiting for this instance
+    /// are connected to it. The asset is unloaded again if the instance can't
+    /// be restored.
+    pub fn restore_instance<P: AsRef<Path>>(
+        &mut self,
+        path: &P,
+        instance: &AssetInstance,
+    ) -> Result<(Uuid, RestoredInstance)> {
+        let asset_id = self.load_asset_file(path, Some(instance.definition.as_str()))?;
+        self.set_asset_dir_path(asset_id, instance.asset_path.clone());
+
+        match self.apply_instance(asset_id, instance) {
+            Ok(restored) => Ok((asset_id, restored)),
+            Err(err) => {
+                if let Err(unload_err) = self.unload_asset(asset_id) {
+                    log::warn!(
+                        "Failed to unload asset {asset_id} after restoring it failed: {unload_err}"
+                    );
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// Restores the parameter values and inputs of a stored instance onto a
+    /// freshly loaded asset.
+    fn apply_instance(
+        &mut self,
+        asset_id: Uuid,
+        instance: &AssetInstance,
+    ) -> Result<RestoredInstance> {
+        let applied = self
+            .try_get_asset(asset_id)?
+            .restore_snapshot(&instance.parameters)
+            .map_err(SessionError::AssetError)?;
+
+        let mut missing_inputs = Vec::new();
+        for input in &instance.inputs {
+            match &input.source {
+                InputSource::Geometry {
+                    geometry,
+                    units_per_stud,
+                } => self.set_asset_input_geometry(
+                    asset_id,
+                    input.input_index,
+                    geometry,
+                    *units_per_stud,
+                )?,
+                InputSource::Asset {
+                    instance_key,
+                    output_index,
+                } => match self.find_instance(instance_key) {
+                    Some(source_asset_id) => self.connect_assets(AssetConnection {
+                        source_asset_id,
+                        output_index: *output_index,
+                        target_asset_id: asset_id,
+                        input_index: input.input_index,
+                    })?,
+                    None => {
+                        self.waiting_inputs.insert(
+                            (asset_id, input.input_index),
+                            (instance_key.clone(), *output_index),
+                        );
+                        missing_inputs.push(input.input_index);
+                    }
+                },
+            }
+        }
+
+        self.instance_keys.insert(asset_id, instance.key.clone());
+        self.cook_options.insert(asset_id, instance.options.clone());
+        self.needs_cook.insert(asset_id);
+
+        let waiting: Vec<AssetConnection> = self
+            .waiting_inputs
+            .iter()
+            .filter(|(_, (instance_key, _))| *instance_key == instance.key)
+            .map(
+                |(&(target_asset_id, input_index), &(_, output_index))| AssetConnection {
+                    source_asset_id: asset_id,
+                    output_index,
+                    target_asset_id,
+                    input_index,
+                },
+            )
+            .collect();
+
+        // A downstream input that can't be connected doesn't stop this
+        // instance from being restored.
+        let mut connected_downstream = Vec::new();
+        for connection in waiting {
+            self.waiting_inputs
+                .remove(&(connection.target_asset_id, connection.input_index));
+            match self.connect_assets(connection.clone()) {
+                Ok(()) => {
+                    self.needs_cook.insert(connection.target_asset_id);
+                    connected_downstream.push(connection);
+                }
+                Err(err) => log::warn!(
+                    "Failed to connect input {} of asset {} to instance {}: {err}",
+                    connection.input_index,
+                    connection.target_asset_id,
+                    instance.key
+                ),
+            }
+        }
+
+        Ok(RestoredInstance {
+            skipped: applied.skipped,
+            missing_inputs,
+            connected_downstream,
+        })
+    }
+
+    /// Unloads an asset, deleting its nodes and everything the session keeps
+    /// about it. Inputs of other assets that it fed are disconnected.
+    fn unload_asset(&mut self, asset_id: Uuid) -> Result<()> {
+        let downstream: Vec<(Uuid, i32)> = self
+            .asset_graph
+            .connections()
+            .iter()
+            .filter(|connection| connection.source_asset_id == asset_id)
+            .map(|connection| (connection.target_asset_id, connection.input_index))
+            .collect();
+        for (target_asset_id, input_index) in downstream {
+            self.disconnect_assets(target_asset_id, input_index)?;
+        }
+
+        let asset = self
+            .asset_db
+            .remove(&asset_id)
+            .ok_or(SessionError::AssetNotFound(asset_id))?;
+        self.asset_graph.remove_asset(asset_id);
+        self.asset_dir_paths.remove(&asset_id);
+        self.param_histories.remove(&asset_id);
+        self.needs_cook.remove(&asset_id);
+        self.instance_keys.remove(&asset_id);
+        self.cook_options.remove(&asset_id);
+        self.input_geometries
+            .retain(|(target_asset_id, _), _| *target_asset_id != asset_id);
+        self.waiting_inputs
+            .retain(|(target_asset_id, _), _| *target_asset_id != asset_id);
+
+        asset.delete().map_err(SessionError::AssetError)
+    }
+
+    /// Lists the changes to an asset's parameters that can be undone and
+    /// redone.
+    pub fn parameter_history(&self, asset_id: Uuid) -> Result<HistoryInfo> {
+        self.try_get_asset(asset_id)?;
+        let history = self.param_histories.get(&asset_id);
+
+        Ok(HistoryInfo {
+            undo: history
+                .map(ParameterHistory::undo_entries)
+                .unwrap_or_default(),
+            redo: history
+                .map(ParameterHistory::redo_entries)
+                .unwrap_or_default(),
+            needs_cook: self.needs_cook.contains(&asset_id),
+        })
+    }
 }